@@ -15,16 +15,183 @@ use candid::{CandidType, Principal};
 use ic_cdk::caller;
 use ic_cdk_macros::*;
 use icrc_nft_types::Account;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::cell::RefCell;
 
 
 use icrc_nft_types::icrc7::transfer::TransferArg;
 use ic_cdk::api::call::call;
+use ic_ledger_types::{
+    AccountIdentifier, ArchivedBlocksRange, Block, BlockIndex, BlockRange, GetBlocksArgs,
+    Operation, QueryBlocksResult, Tokens, DEFAULT_SUBACCOUNT,
+};
+use ic_cdk::api::management_canister::ecdsa::{
+    ecdsa_public_key, sign_with_ecdsa, EcdsaCurve, EcdsaKeyId, EcdsaPublicKeyArgument,
+    SignWithEcdsaArgument,
+};
+use ic_cdk::api::management_canister::main::raw_rand;
+use k256::ecdsa::signature::hazmat::PrehashVerifier;
+use k256::ecdsa::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256, Sha512};
+// DANE/TLSA certificate verification (`verify_cert_dane`) needs an X.509 DER parser to pull
+// out the SubjectPublicKeyInfo; requires `x509-parser = "0.16"` as a dependency.
+use x509_parser::prelude::*;
 
 type TokenId = u64;
 
+// Enum describing when a delegated approval stops being valid, following the DIP-721
+// Owner/Operator/Custodian model and cw721's `Expiration` type.
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Expiration {
+    Never,
+    AtTime(u64),
+    AtHeight(u64),
+}
+
+impl Expiration {
+    fn is_expired(&self, now_nanos: u64, height: u64) -> bool {
+        match self {
+            Expiration::Never => false,
+            Expiration::AtTime(at) => now_nanos >= *at,
+            Expiration::AtHeight(at) => height >= *at,
+        }
+    }
+}
+
+// Records why and by whom a certification was revoked, following SNIP-721's burn/un-burn
+// distinction: any manager may burn, but only the contract owner may later un-burn.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct BurnRecord {
+    pub revoker: Principal,
+    pub burned_at: u64,
+    pub reason: Option<String>,
+    pub owner: Principal,
+}
+
+// The kind of certification lifecycle event recorded in `CertificationNFT::tx_log`, following
+// SNIP-721's `store_mint` / `store_transfer` / `store_burn` transaction history.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum CertEventKind {
+    Mint,
+    MintBatch,
+    Transfer,
+    Revoke,
+    Unburn,
+    GrantManager,
+    RevokeManager,
+    SetBaseUri,
+}
+
+/**
+ * @dev A single entry in the on-chain audit log, letting a verifier reconstruct who
+ * minted/transferred/revoked a given certification and when.
+ * @param kind The lifecycle event that occurred.
+ * @param principal The Principal that performed the action.
+ * @param token_ids The token IDs affected by the event, if any.
+ * @param target The target account of the event (e.g. a transfer recipient or a granted manager), if any.
+ * @param timestamp When the event was recorded.
+ */
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CertEvent {
+    pub kind: CertEventKind,
+    pub principal: Principal,
+    pub token_ids: Vec<u128>,
+    pub target: Option<Principal>,
+    pub timestamp: u64,
+}
+
+
+// The threshold-ECDSA key used to sign provenance manifests. On mainnet this should be
+// "key_1"; locally (dfx replica) it is "dfx_test_key".
+const ECDSA_KEY_NAME: &str = "dfx_test_key";
+
+fn ecdsa_key_id() -> EcdsaKeyId {
+    EcdsaKeyId {
+        curve: EcdsaCurve::Secp256k1,
+        name: ECDSA_KEY_NAME.to_string(),
+    }
+}
+
+fn sha256(bytes: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    hasher.finalize().to_vec()
+}
+
+fn sha512(bytes: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha512::new();
+    hasher.update(bytes);
+    hasher.finalize().to_vec()
+}
+
+// Hashes an assertion's label and data together; the label is length-prefixed so the
+// boundary between the two cannot shift without changing the hash.
+fn assertion_hash(assertion: &Assertion) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update((assertion.label.len() as u64).to_be_bytes());
+    hasher.update(assertion.label.as_bytes());
+    hasher.update(&assertion.data);
+    hasher.finalize().to_vec()
+}
+
+// A single C2PA-style assertion bundled into a `Manifest`, e.g. a `c2pa.actions`-like list
+// or a `c2pa.hash.data` binding of the asset hash.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Assertion {
+    pub label: String,
+    pub data: Vec<u8>,
+}
+
+/**
+ * @dev The claim object a manifest's signature is computed over: the asset hash plus the
+ * SHA-256 of every assertion (its label and data), so tampering with either is detectable.
+ */
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Claim {
+    pub asset_hash: Vec<u8>,
+    pub assertion_hashes: Vec<Vec<u8>>,
+}
+
+// A COSE_Sign1-like envelope holding the claim signature and the signing principal.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct CoseSign1 {
+    pub signature: Vec<u8>,
+    pub signer: Principal,
+}
+
+/**
+ * @dev A small, self-contained, tamper-evident provenance manifest in the style of the C2PA
+ * content-authenticity model, returned by `certify_with_manifest` and verifiable off-chain.
+ */
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Manifest {
+    pub assertions: Vec<Assertion>,
+    pub claim: Claim,
+    pub claim_signature: CoseSign1,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum VerificationResult {
+    Valid,
+    Invalid(String),
+}
+
+// A DNS TLSA resource record (RFC 6698), supplied by the caller since the canister cannot
+// perform DNS resolution itself; see `verify_cert_dane`.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TlsaRecord {
+    pub usage: u8,
+    pub selector: u8,
+    pub matching_type: u8,
+    pub association_data: Vec<u8>,
+}
+
+// TLSA certificate usage values this canister is willing to anchor trust on directly, since
+// it has no classic CA chain to validate PKIX-TA(0)/PKIX-EE(1) against.
+const DANE_TA: u8 = 2;
+const DANE_EE: u8 = 3;
+
 
 
 // Enum to represent the result of a transfer operation.
@@ -68,13 +235,15 @@ pub struct TokenUriArgs {
  * @param name The name of the NFT.
  * @param description An optional description of the NFT.
  * @param logo An optional logo URL for the NFT.
+ * @param expires_at An optional expiry, in nanoseconds since epoch, after which the certification is no longer valid.
  */
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct MintArgs {
-    pub owner: Account,     
+    pub owner: Account,
     pub name: String,
     pub description: Option<String>,
     pub logo: Option<String>,
+    pub expires_at: Option<u64>,
 }
 
 
@@ -85,6 +254,7 @@ pub struct MintArgs {
  * @param names A vector of names for the NFTs.
  * @param descriptions A vector of optional descriptions for the NFTs.
  * @param logos A vector of optional logos for the NFTs.
+ * @param expires_at A vector of optional expiries, in nanoseconds since epoch, one per NFT.
  */
  #[derive(CandidType, Deserialize, Clone, Debug)]
  pub struct MintBatchArgs {
@@ -92,8 +262,34 @@ pub struct MintArgs {
      pub names: Vec<String>,
      pub descriptions: Vec<Option<String>>,
      pub logos: Vec<Option<String>>,
+     pub expires_at: Vec<Option<u64>>,
  }
 
+/**
+ * @dev Struct to define the arguments for burning a single NFT, mirroring the NFT
+ * canister's own `icrc7_burn` argument shape.
+ * @param token_id The ID of the token to burn.
+ * @param from_subaccount The subaccount of the current owner the token is burned from.
+ * @param memo An optional memo recorded on the burn block.
+ */
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct BurnArg {
+    pub token_id: u128,
+    pub from_subaccount: Option<[u8; 32]>,
+    pub memo: Option<Vec<u8>>,
+}
+
+// A client certificate bound to a caller principal via `register_client_cert`, gating
+// privileged operations on proof of possession of the matching private key.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ClientCertAuth {
+    pub fingerprint: Vec<u8>,
+    pub public_key: Vec<u8>,
+}
+
+// How long a nonce from `request_auth_nonce` remains valid before it must be re-requested.
+const AUTH_NONCE_TTL_NANOS: u64 = 5 * 60 * 1_000_000_000;
+
 // Struct representing the CertificationNFT, which includes various mappings for managing NFTs.
  /**
  * @dev Struct representing the CertificationNFT, which includes various mappings for managing NFTs.
@@ -104,7 +300,7 @@ pub struct MintArgs {
  * @param tokens Tracks the number of tokens for each Principal.
  * @param next_token_id Tracks the next available Token ID.
  */
-#[derive(Clone)]
+#[derive(CandidType, Deserialize, Clone, Debug)]
 struct CertificationNFT {
     owner: Principal,
     is_manager: HashMap<Principal, bool>,
@@ -113,6 +309,33 @@ struct CertificationNFT {
     tokens: HashMap<u64, Principal>, // Tracks the number of tokens for each principal
     next_token_id: u64, // Tracks the next token ID
 
+    // owner -> operator -> expiry, an "approve-all" grant an NFT owner makes for their own tokens.
+    operators: HashMap<Principal, HashMap<Principal, Expiration>>,
+    // token -> (operator, expiry), a single-token approval.
+    token_approvals: HashMap<TokenId, (Principal, Expiration)>,
+    // Logical clock backing `Expiration::AtHeight`, advanced by `approve`, `approve_all` and `record_burn`.
+    height: u64,
+    // Tokens that have been revoked (burned), keyed for un-burn validation.
+    burned: HashMap<TokenId, BurnRecord>,
+    // Append-only audit log of certification lifecycle events.
+    tx_log: Vec<CertEvent>,
+    // Expiry timestamp (nanoseconds since epoch) for tokens that expire. A token with no
+    // entry here never expires.
+    expiry: HashMap<TokenId, u64>,
+    // ICP payment required before `mint_paid` will proceed to mint.
+    mint_fee: Tokens,
+    // The account `mint_paid` payments must be sent to.
+    fee_recipient: AccountIdentifier,
+    // Ledger block indexes already consumed by `mint_paid`, guarding against replay.
+    consumed_blocks: HashSet<BlockIndex>,
+    // SEC1-encoded threshold-ECDSA public key, fetched and cached on first
+    // `certify_with_manifest` call so `verify_manifest` can stay a plain query.
+    ecdsa_public_key: Option<Vec<u8>>,
+    // Registered client certificates, keyed by the IC principal they're bound to.
+    client_certs: HashMap<Principal, ClientCertAuth>,
+    // Outstanding auth nonces issued by `request_auth_nonce`, keyed by caller, holding
+    // (nonce, expiry timestamp in nanoseconds since epoch).
+    auth_nonces: HashMap<Principal, (Vec<u8>, u64)>,
 }
 
 
@@ -130,7 +353,18 @@ impl Default for CertificationNFT {
             owned_tokens: HashMap::new(),
             tokens: HashMap::new(),
             next_token_id: 1,
-
+            operators: HashMap::new(),
+            token_approvals: HashMap::new(),
+            height: 0,
+            burned: HashMap::new(),
+            tx_log: Vec::new(),
+            expiry: HashMap::new(),
+            mint_fee: Tokens::from_e8s(0),
+            fee_recipient: AccountIdentifier::new(&Principal::anonymous(), &DEFAULT_SUBACCOUNT),
+            consumed_blocks: HashSet::new(),
+            ecdsa_public_key: None,
+            client_certs: HashMap::new(),
+            auth_nonces: HashMap::new(),
         }
     }
 }
@@ -161,6 +395,7 @@ impl CertificationNFT {
             return Err("Error: Already a manager".to_string());
         }
         self.is_manager.insert(manager, true);
+        self.log_event(CertEventKind::GrantManager, caller, vec![], Some(manager));
         Ok(())
     }
     
@@ -191,7 +426,8 @@ impl CertificationNFT {
         
         // Revoke manager rights
         self.is_manager.insert(manager, false);
-        
+        self.log_event(CertEventKind::RevokeManager, caller, vec![], Some(manager));
+
         Ok(())
     }
 
@@ -221,6 +457,347 @@ impl CertificationNFT {
         }
     }
 
+    /// Advances the logical clock backing `Expiration::AtHeight`. Called by `approve`,
+    /// `approve_all` and `record_burn`; other state changes do not advance it.
+    fn tick_height(&mut self) -> u64 {
+        self.height += 1;
+        self.height
+    }
+
+    /// True if `operator` currently holds a non-expired approve-all grant from `owner`.
+    fn is_operator_active(&self, owner: Principal, operator: Principal) -> bool {
+        let now = ic_cdk::api::time();
+        self.operators
+            .get(&owner)
+            .and_then(|grants| grants.get(&operator))
+            .is_some_and(|expiry| !expiry.is_expired(now, self.height))
+    }
+
+    /// True if `operator` currently holds a non-expired single-token approval for `token_id`.
+    fn is_token_approval_active(&self, token_id: TokenId, operator: Principal) -> bool {
+        let now = ic_cdk::api::time();
+        match self.token_approvals.get(&token_id) {
+            Some((approved, expiry)) => {
+                *approved == operator && !expiry.is_expired(now, self.height)
+            }
+            None => false,
+        }
+    }
+
+    /// Same as `is_operator_active`, but removes the grant if it has expired.
+    fn prune_operator(&mut self, owner: Principal, operator: Principal) -> bool {
+        let active = self.is_operator_active(owner, operator);
+        if !active {
+            if let Some(grants) = self.operators.get_mut(&owner) {
+                grants.remove(&operator);
+            }
+        }
+        active
+    }
+
+    /// Same as `is_token_approval_active`, but removes the approval if it has expired.
+    fn prune_token_approval(&mut self, token_id: TokenId, operator: Principal) -> bool {
+        let active = self.is_token_approval_active(token_id, operator);
+        if !active {
+            if self.token_approvals.get(&token_id).map(|(approved, _)| *approved) == Some(operator) {
+                self.token_approvals.remove(&token_id);
+            }
+        }
+        active
+    }
+
+    /// Grants `operator` a single-token approval. Only the token's owner (as tracked in
+    /// `token_owner`) may approve on it.
+    fn approve(&mut self, token_id: TokenId, operator: Principal, expires_at: Expiration) -> Result<(), String> {
+        if self.is_burned(token_id) {
+            return Err("TokenBurned".to_string());
+        }
+        let caller = ic_cdk::caller();
+        match self.token_owner.get(&token_id) {
+            Some(owner) if *owner == caller => {
+                self.token_approvals.insert(token_id, (operator, expires_at));
+                self.tick_height();
+                Ok(())
+            }
+            Some(_) => Err("UnauthorizedApprove".to_string()),
+            None => Err("NonExistingTokenId".to_string()),
+        }
+    }
+
+    /// Grants `operator` an approve-all over every token the caller owns.
+    fn approve_all(&mut self, operator: Principal, expires_at: Expiration) {
+        let caller = ic_cdk::caller();
+        self.operators
+            .entry(caller)
+            .or_default()
+            .insert(operator, expires_at);
+        self.tick_height();
+    }
+
+    /// Revokes a single-token approval (if `token_id` is set) or an approve-all grant
+    /// (otherwise) that the caller previously made for `operator`.
+    fn revoke_approval(&mut self, token_id: Option<TokenId>, operator: Principal) -> Result<(), String> {
+        let caller = ic_cdk::caller();
+        match token_id {
+            Some(token_id) => match self.token_owner.get(&token_id) {
+                Some(owner) if *owner == caller => {
+                    if self.token_approvals.get(&token_id).map(|(approved, _)| *approved) == Some(operator) {
+                        self.token_approvals.remove(&token_id);
+                    }
+                    Ok(())
+                }
+                Some(_) => Err("UnauthorizedRevokeApproval".to_string()),
+                None => Err("NonExistingTokenId".to_string()),
+            },
+            None => {
+                if let Some(grants) = self.operators.get_mut(&caller) {
+                    grants.remove(&operator);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the operator currently approved for `token_id`, pruning it first if expired.
+    fn get_approved(&mut self, token_id: TokenId) -> Option<Principal> {
+        let operator = self.token_approvals.get(&token_id).map(|(operator, _)| *operator)?;
+        self.prune_token_approval(token_id, operator).then_some(operator)
+    }
+
+    /// True if `caller` may act on `token_id`: a manager, the tracked token owner, or a
+    /// non-expired per-token or approve-all operator.
+    fn is_authorized_for_token(&self, caller: Principal, token_id: TokenId) -> bool {
+        if self.is_manager.get(&caller).copied().unwrap_or(false) {
+            return true;
+        }
+        let Some(owner) = self.token_owner.get(&token_id).copied() else {
+            return false;
+        };
+        if owner == caller {
+            return true;
+        }
+        self.is_token_approval_active(token_id, caller) || self.is_operator_active(owner, caller)
+    }
+
+    /// True if `token_id` has an outstanding burn record and has not since been un-burned.
+    fn is_burned(&self, token_id: TokenId) -> bool {
+        self.burned.contains_key(&token_id)
+    }
+
+    /// Records that `token_id` was burned by `revoker`, capturing its last known owner so a
+    /// later `unburn_certification` can restore it.
+    fn record_burn(&mut self, token_id: TokenId, revoker: Principal, reason: Option<String>) {
+        let owner = self.token_owner.get(&token_id).copied().unwrap_or(revoker);
+        self.burned.insert(
+            token_id,
+            BurnRecord {
+                revoker,
+                burned_at: ic_cdk::api::time(),
+                reason,
+                owner,
+            },
+        );
+        self.token_owner.remove(&token_id);
+        if let Some(tokens) = self.owned_tokens.get_mut(&owner) {
+            tokens.remove(&token_id);
+        }
+        self.tick_height();
+    }
+
+    /// Records a freshly minted token's owner in the local ownership index.
+    fn record_mint(&mut self, token_id: TokenId, owner: Principal) {
+        self.token_owner.insert(token_id, owner);
+        self.owned_tokens.entry(owner).or_default().insert(token_id);
+    }
+
+    /// Moves a token between owners in the local ownership index after a successful transfer.
+    fn record_transfer(&mut self, token_id: TokenId, to: Principal) {
+        if let Some(from) = self.token_owner.insert(token_id, to) {
+            if let Some(tokens) = self.owned_tokens.get_mut(&from) {
+                tokens.remove(&token_id);
+            }
+        }
+        self.owned_tokens.entry(to).or_default().insert(token_id);
+    }
+
+    /// Returns every token id `owner` currently holds, per the local ownership index.
+    fn tokens_of(&self, owner: Principal) -> Vec<TokenId> {
+        self.owned_tokens
+            .get(&owner)
+            .map(|tokens| tokens.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the tracked owner of `token_id`, if any.
+    fn owner_of(&self, token_id: TokenId) -> Option<Principal> {
+        self.token_owner.get(&token_id).copied()
+    }
+
+    /// Returns the number of tokens currently tracked as held by some owner.
+    fn total_supply(&self) -> u64 {
+        self.token_owner.len() as u64
+    }
+
+    /// Appends an entry to the audit log. Called at the end of every successful
+    /// `call_icrc7_*` proxy and the manager-grant/revoke paths.
+    fn log_event(&mut self, kind: CertEventKind, principal: Principal, token_ids: Vec<u128>, target: Option<Principal>) {
+        self.tx_log.push(CertEvent {
+            kind,
+            principal,
+            token_ids,
+            target,
+            timestamp: ic_cdk::api::time(),
+        });
+    }
+
+    /// Returns up to `limit` audit log entries starting at `start`, for paginated retrieval.
+    fn get_tx_log(&self, start: u64, limit: u64) -> Vec<CertEvent> {
+        self.tx_log
+            .iter()
+            .skip(start as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns every audit log entry that mentions `token_id`, in recording order.
+    fn get_token_history(&self, token_id: u128) -> Vec<CertEvent> {
+        self.tx_log
+            .iter()
+            .filter(|event| event.token_ids.contains(&token_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Records `token_id`'s expiry, if it has one. A token with no entry never expires.
+    fn record_expiry(&mut self, token_id: TokenId, expires_at: Option<u64>) {
+        if let Some(expires_at) = expires_at {
+            self.expiry.insert(token_id, expires_at);
+        }
+    }
+
+    /// True if `token_id` is either non-expiring or has not yet reached its expiry.
+    fn is_valid(&self, token_id: TokenId) -> bool {
+        match self.expiry.get(&token_id) {
+            Some(expires_at) => ic_cdk::api::time() < *expires_at,
+            None => true,
+        }
+    }
+
+    /// Returns `token_id`'s expiry timestamp, if it has one.
+    fn expiry_of(&self, token_id: TokenId) -> Option<u64> {
+        self.expiry.get(&token_id).copied()
+    }
+
+    /// Replaces `token_id`'s expiry. Managers only.
+    fn renew(&mut self, token_id: TokenId, new_expiry: u64) -> Result<(), String> {
+        self.is_caller_manager("UnauthorizedRenew")?;
+        self.expiry.insert(token_id, new_expiry);
+        Ok(())
+    }
+
+    /// True if `block_index` has already been consumed by a prior `mint_paid` call.
+    fn is_block_consumed(&self, block_index: BlockIndex) -> bool {
+        self.consumed_blocks.contains(&block_index)
+    }
+
+    /// Marks `block_index` as consumed, so it cannot be replayed into a second mint.
+    fn mark_block_consumed(&mut self, block_index: BlockIndex) {
+        self.consumed_blocks.insert(block_index);
+    }
+
+    /// Releases a reservation made by `mark_block_consumed` when the payment it guarded
+    /// turned out not to validate, so a legitimate retry isn't permanently blocked.
+    fn unmark_block_consumed(&mut self, block_index: BlockIndex) {
+        self.consumed_blocks.remove(&block_index);
+    }
+
+    /// Sets the ICP amount `mint_paid` requires per mint. Only the contract owner may call this,
+    /// and must additionally present a client-certificate signature per `require_client_cert_auth`.
+    fn set_mint_fee(&mut self, fee: Tokens, signature: &[u8]) -> Result<(), String> {
+        if ic_cdk::caller() != self.owner {
+            return Err("UnauthorizedSetMintFee".to_string());
+        }
+        self.require_client_cert_auth(ic_cdk::caller(), signature, ic_cdk::api::time())?;
+        self.mint_fee = fee;
+        Ok(())
+    }
+
+    /// Sets the account `mint_paid` payments must be sent to. Only the contract owner may call
+    /// this, and must additionally present a client-certificate signature per
+    /// `require_client_cert_auth`.
+    fn set_fee_recipient(&mut self, recipient: AccountIdentifier, signature: &[u8]) -> Result<(), String> {
+        if ic_cdk::caller() != self.owner {
+            return Err("UnauthorizedSetFeeRecipient".to_string());
+        }
+        self.require_client_cert_auth(ic_cdk::caller(), signature, ic_cdk::api::time())?;
+        self.fee_recipient = recipient;
+        Ok(())
+    }
+
+    /// Binds the SHA-256 fingerprint of a self-signed client certificate to `caller`, so
+    /// `require_client_cert_auth` can later demand proof of possession of its private key.
+    fn register_client_cert(&mut self, caller: Principal, der_cert: &[u8]) -> Result<(), String> {
+        let (_, cert) =
+            parse_x509_certificate(der_cert).map_err(|e| format!("InvalidCertificate: {:?}", e))?;
+        let public_key = cert.public_key().subject_public_key.data.to_vec();
+        self.client_certs.insert(
+            caller,
+            ClientCertAuth {
+                fingerprint: sha256(der_cert),
+                public_key,
+            },
+        );
+        Ok(())
+    }
+
+    /// Records a freshly issued auth nonce for `caller`, superseding any unconsumed one.
+    fn issue_auth_nonce(&mut self, caller: Principal, nonce: Vec<u8>, now: u64) {
+        self.auth_nonces
+            .insert(caller, (nonce, now + AUTH_NONCE_TTL_NANOS));
+    }
+
+    /// Gates a privileged operation on proof of possession of the private key matching
+    /// `caller`'s registered client certificate: a signature over the most recent nonce
+    /// `request_auth_nonce` issued to them. The nonce is consumed either way, so a stale or
+    /// failed attempt cannot be replayed.
+    fn require_client_cert_auth(
+        &mut self,
+        caller: Principal,
+        signature: &[u8],
+        now: u64,
+    ) -> Result<(), String> {
+        let auth = self
+            .client_certs
+            .get(&caller)
+            .ok_or("UnregisteredClientCert")?
+            .clone();
+        let (nonce, expires_at) = self
+            .auth_nonces
+            .remove(&caller)
+            .ok_or("NoNonceIssued")?;
+        if now >= expires_at {
+            return Err("NonceExpired".to_string());
+        }
+
+        let verifying_key = VerifyingKey::from_sec1_bytes(&auth.public_key)
+            .map_err(|_| "InvalidPublicKey".to_string())?;
+        let signature =
+            Signature::from_slice(signature).map_err(|_| "InvalidSignatureEncoding".to_string())?;
+        verifying_key
+            .verify_prehash(&sha256(&nonce), &signature)
+            .map_err(|_| "SignatureMismatch".to_string())
+    }
+
+    /// The canister's cached threshold-ECDSA public key, if it has been fetched yet.
+    fn cached_ecdsa_public_key(&self) -> Option<Vec<u8>> {
+        self.ecdsa_public_key.clone()
+    }
+
+    /// Caches the canister's threshold-ECDSA public key so `verify_manifest` can stay a query.
+    fn set_cached_ecdsa_public_key(&mut self, public_key: Vec<u8>) {
+        self.ecdsa_public_key = Some(public_key);
+    }
 
 
 
@@ -340,6 +917,22 @@ impl CertificationNFT {
     ) -> Result<u128, String> {
         self.is_caller_manager("UnauthorizedMint")?;  // Ensures that the caller is manager.
 
+        self.call_icrc7_mint_unchecked(canister_id, args).await
+    }
+
+    /**
+     * @dev Same as `call_icrc7_mint` but without the manager check, for flows that have
+     * already authorized the caller a different way (e.g. `mint_paid`'s ICP payment proof).
+     * Not exposed directly as a canister endpoint; callers must enforce their own authorization.
+     * @param canister_id The Principal of the canister to call.
+     * @param args The arguments containing the details of the NFT to mint.
+     * @return Result<u128, String> Returns the minted token ID or an error message.
+     */
+    async fn call_icrc7_mint_unchecked(
+        &self,
+        canister_id: Principal,
+        args: MintArgs
+    ) -> Result<u128, String> {
         ic_cdk::println!("Calling mint on canister: {:?}", canister_id);
         ic_cdk::println!("MintArgs: {:?}", args);
 
@@ -417,7 +1010,21 @@ impl CertificationNFT {
         caller: Account,
         args: Vec<TransferArg>,
     ) -> Result<Vec<Result<u128, String>>, String> {
-        self.is_caller_manager("UnauthorizedTransfer")?; // Works similar to _beforeTokenTransfer function Ensures that the caller is manager
+        // A caller passes if they are a manager, the tracked owner of every token being
+        // transferred, or a non-expired approved operator for each of them.
+        let ic_caller = ic_cdk::caller();
+        for arg in &args {
+            if self.is_burned(arg.token_id as TokenId) {
+                return Err("TokenBurned".to_string());
+            }
+        }
+        if !self.is_manager.get(&ic_caller).copied().unwrap_or(false) {
+            for arg in &args {
+                if !self.is_authorized_for_token(ic_caller, arg.token_id as TokenId) {
+                    return Err("UnauthorizedTransfer".to_string());
+                }
+            }
+        }
 
         ic_cdk::println!("Calling icrc7_transfer on canister: {:?}", canister_id);
         ic_cdk::println!("TransferArgs: {:?}", args);
@@ -437,6 +1044,40 @@ impl CertificationNFT {
     }
 
 
+    /**
+     * @dev Asynchronously calls the `icrc7_burn` method on another canister to revoke
+     * certifications. Only managers can burn.
+     * @param canister_id The Principal of the canister to call.
+     * @param caller The account the tokens are burned from.
+     * @param args The arguments containing the details of the burn, including token IDs.
+     * @return Result<Vec<Result<u128, String>>, String> Returns a vector of results for each burn or an error message.
+     */
+    async fn call_icrc7_burn(
+        &self,
+        canister_id: Principal,
+        caller: Account,
+        args: Vec<BurnArg>,
+    ) -> Result<Vec<Result<u128, String>>, String> {
+        self.is_caller_manager("UnauthorizedBurn")?;  // Ensures that the caller is manager.
+
+        ic_cdk::println!("Calling icrc7_burn on canister: {:?}", canister_id);
+        ic_cdk::println!("BurnArgs: {:?}", args);
+
+        let result: Result<(Vec<Result<u128, String>>,), _> = call(canister_id, "icrc7_burn", (caller, args)).await;
+
+        match result {
+            Ok((burn_results,)) => {
+                ic_cdk::println!("Burn successful: {:?}", burn_results);
+                Ok(burn_results)
+            },
+            Err(e) => {
+                ic_cdk::println!("Burn failed: {:?}", e);
+                Err(format!("Failed to call icrc7_burn: {:?}", e))
+            }
+        }
+    }
+
+
 
 
 
@@ -452,6 +1093,43 @@ thread_local! {
     static STATE: std::cell::RefCell<Option<CertificationNFT>> = std::cell::RefCell::new(None);
 }
 
+// Versioned wrapper around `CertificationNFT` written to stable memory across upgrades. Adding
+// a field to `CertificationNFT` later should add a new `V2(...)` variant here plus a migration
+// arm in `post_upgrade`, rather than breaking canisters upgrading from a `V1` snapshot.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+enum StableState {
+    V1(CertificationNFT),
+}
+
+/**
+ * @dev Runs immediately before an upgrade. Serializes the whole in-memory state into stable
+ * memory so managers and any locally-tracked index survive `dfx deploy --upgrade`.
+ */
+#[pre_upgrade]
+fn pre_upgrade() {
+    let contract = STATE.with(|state| state.borrow().clone());
+    let stable_state = contract.map(StableState::V1);
+    ic_cdk::storage::stable_save((stable_state,))
+        .expect("Failed to save CertificationNFT state to stable memory");
+}
+
+/**
+ * @dev Runs immediately after an upgrade. Restores the state saved by `pre_upgrade`.
+ */
+#[post_upgrade]
+fn post_upgrade() {
+    let (stable_state,): (Option<StableState>,) = ic_cdk::storage::stable_restore()
+        .expect("Failed to restore CertificationNFT state from stable memory");
+
+    let contract = stable_state.map(|versioned| match versioned {
+        StableState::V1(contract) => contract,
+    });
+
+    STATE.with(|state| {
+        *state.borrow_mut() = contract;
+    });
+}
+
 
 
 /**
@@ -473,7 +1151,18 @@ fn init() {
 
         tokens: HashMap::new(),
         next_token_id: 1,
-
+        operators: HashMap::new(),
+        token_approvals: HashMap::new(),
+        height: 0,
+        burned: HashMap::new(),
+        tx_log: Vec::new(),
+        expiry: HashMap::new(),
+        mint_fee: Tokens::from_e8s(0),
+        fee_recipient: AccountIdentifier::new(&ic_cdk::id(), &DEFAULT_SUBACCOUNT),
+        consumed_blocks: HashSet::new(),
+        ecdsa_public_key: None,
+        client_certs: HashMap::new(),
+        auth_nonces: HashMap::new(),
     };
 
     STATE.with(|state| {
@@ -517,46 +1206,139 @@ fn revokeManager(manager: Principal) -> Result<(), String> {
 }
 
 
-
-
-
-
-
 /**
- * @dev Asynchronously calls the `base_uri` method on another canister to retrieve the base URI.
- * @param canister_id The Principal of the canister to call.
- * @return Result<String, String> Returns the base URI or an error message.
+ * @dev Approves `operator` to act on a single token on the caller's behalf until `expires_at`.
+ * @param token_id The ID of the token to approve.
+ * @param operator The Principal being granted the approval.
+ * @param expires_at When the approval stops being valid.
+ * @return Result<(), String> Returns Ok if successful, or an error message.
  */
 #[ic_cdk::update]
-async fn baseURI(canister_id: Principal) -> Result<String, String> {
-    let state_clone = STATE.with(|state| state.borrow().clone());
-    if let Some(contract) = state_clone {
-        contract.call_icrc7_base_uri(canister_id).await     // Call the async base_uri method.
-    } else {
-        Err("Contract not initialized".to_string())
-    }
+fn approve(token_id: TokenId, operator: Principal, expires_at: Expiration) -> Result<(), String> {
+    STATE.with(|state| {
+        if let Some(contract) = state.borrow_mut().as_mut() {
+            contract.approve(token_id, operator, expires_at)
+        } else {
+            Err("Contract not initialized".to_string())
+        }
+    })
 }
 
+
 /**
- * @dev Asynchronously calls the `set_base_uri` method on another canister to set a new base URI.
- * @param canister_id The Principal of the canister to call.
- * @param uri The new base URI to be set.
- * @return Result<(), String> Returns Ok if successful, or an error message.
+ * @dev Approves `operator` to act on every token the caller owns until `expires_at`.
+ * @param operator The Principal being granted the approve-all grant.
+ * @param expires_at When the approval stops being valid.
  */
 #[ic_cdk::update]
-async fn setBaseURI(canister_id: Principal, uri: String) -> Result<(), String> {
-    let args = SetBaseUriArgs { uri };
-    let state_clone = STATE.with(|state| state.borrow().clone());
-    if let Some(contract) = state_clone {
-        contract.call_icrc7_set_base_uri(canister_id, args).await       // Call the async set_base_uri method.
-    } else {
-        Err("Contract not initialized".to_string())
-    }
+fn approve_all(operator: Principal, expires_at: Expiration) {
+    STATE.with(|state| {
+        if let Some(contract) = state.borrow_mut().as_mut() {
+            contract.approve_all(operator, expires_at);
+        }
+    })
 }
 
+
 /**
- * @dev Asynchronously calls the `token_uri` method on another canister to retrieve the token URI for a specific token ID.
- * @param canister_id The Principal of the canister to call.
+ * @dev Revokes a previously granted approval for `operator`. If `token_id` is set, only the
+ * single-token approval is revoked; otherwise the caller's approve-all grant is revoked.
+ * @param token_id The token whose approval should be revoked, or None for an approve-all grant.
+ * @param operator The Principal whose approval is being revoked.
+ * @return Result<(), String> Returns Ok if successful, or an error message.
+ */
+#[ic_cdk::update]
+fn revoke_approval(token_id: Option<TokenId>, operator: Principal) -> Result<(), String> {
+    STATE.with(|state| {
+        if let Some(contract) = state.borrow_mut().as_mut() {
+            contract.revoke_approval(token_id, operator)
+        } else {
+            Err("Contract not initialized".to_string())
+        }
+    })
+}
+
+
+/**
+ * @dev Returns the operator currently approved for `token_id`, if any, pruning it first if expired.
+ * @param token_id The ID of the token to look up.
+ * @return Option<Principal> The approved operator, or None if there isn't one.
+ */
+#[ic_cdk::update]
+fn get_approved(token_id: TokenId) -> Option<Principal> {
+    STATE.with(|state| {
+        state
+            .borrow_mut()
+            .as_mut()
+            .and_then(|contract| contract.get_approved(token_id))
+    })
+}
+
+
+/**
+ * @dev Returns whether `operator` currently holds a non-expired approve-all grant from
+ * `owner`, pruning it first if expired.
+ * @param owner The account that may have granted the approve-all.
+ * @param operator The principal to check for an active grant.
+ * @return bool True if `operator` is currently approved for all of `owner`'s tokens.
+ */
+#[ic_cdk::update]
+fn is_approved_for_all(owner: Principal, operator: Principal) -> bool {
+    STATE.with(|state| {
+        state
+            .borrow_mut()
+            .as_mut()
+            .is_some_and(|contract| contract.prune_operator(owner, operator))
+    })
+}
+
+
+
+
+
+/**
+ * @dev Asynchronously calls the `base_uri` method on another canister to retrieve the base URI.
+ * @param canister_id The Principal of the canister to call.
+ * @return Result<String, String> Returns the base URI or an error message.
+ */
+#[ic_cdk::update]
+async fn baseURI(canister_id: Principal) -> Result<String, String> {
+    let state_clone = STATE.with(|state| state.borrow().clone());
+    if let Some(contract) = state_clone {
+        contract.call_icrc7_base_uri(canister_id).await     // Call the async base_uri method.
+    } else {
+        Err("Contract not initialized".to_string())
+    }
+}
+
+/**
+ * @dev Asynchronously calls the `set_base_uri` method on another canister to set a new base URI.
+ * @param canister_id The Principal of the canister to call.
+ * @param uri The new base URI to be set.
+ * @return Result<(), String> Returns Ok if successful, or an error message.
+ */
+#[ic_cdk::update]
+async fn setBaseURI(canister_id: Principal, uri: String) -> Result<(), String> {
+    let args = SetBaseUriArgs { uri };
+    let state_clone = STATE.with(|state| state.borrow().clone());
+    let Some(contract) = state_clone else {
+        return Err("Contract not initialized".to_string());
+    };
+
+    contract.call_icrc7_set_base_uri(canister_id, args).await?;       // Call the async set_base_uri method.
+
+    STATE.with(|state| {
+        if let Some(contract) = state.borrow_mut().as_mut() {
+            contract.log_event(CertEventKind::SetBaseUri, ic_cdk::caller(), vec![], None);
+        }
+    });
+
+    Ok(())
+}
+
+/**
+ * @dev Asynchronously calls the `token_uri` method on another canister to retrieve the token URI for a specific token ID.
+ * @param canister_id The Principal of the canister to call.
  * @param token_id The ID of the token for which to retrieve the URI.
  * @return Result<String, String> Returns the token URI or an error message.
  */
@@ -602,6 +1384,383 @@ fn get_managers() -> Vec<Principal> {
     })
 }
 
+
+/**
+ * @dev Retrieves every token a principal holds, per this canister's local ownership index.
+ * @param owner The Principal to look up.
+ * @return Vec<u64> The token IDs `owner` holds.
+ */
+#[ic_cdk::query]
+fn tokens_of(owner: Principal) -> Vec<TokenId> {
+    STATE.with(|state| {
+        state.borrow().as_ref().map_or(vec![], |contract| {
+            contract.tokens_of(owner)
+        })
+    })
+}
+
+
+/**
+ * @dev Retrieves the tracked owner of a token, per this canister's local ownership index.
+ * @param token_id The ID of the token to look up.
+ * @return Option<Principal> The owning Principal, or None if the token isn't tracked.
+ */
+#[ic_cdk::query]
+fn owner_of(token_id: u128) -> Option<Principal> {
+    STATE.with(|state| {
+        state
+            .borrow()
+            .as_ref()
+            .and_then(|contract| contract.owner_of(token_id as TokenId))
+    })
+}
+
+
+/**
+ * @dev Retrieves the number of tokens currently tracked as held by some owner.
+ * @return u64 The total supply, per this canister's local ownership index.
+ */
+#[ic_cdk::query]
+fn total_supply() -> u64 {
+    STATE.with(|state| {
+        state.borrow().as_ref().map_or(0, |contract| contract.total_supply())
+    })
+}
+
+
+/**
+ * @dev Retrieves a page of the on-chain audit log, letting a verifier walk through every
+ * certification lifecycle event in the order it was recorded.
+ * @param start The index of the first entry to return.
+ * @param limit The maximum number of entries to return.
+ * @return Vec<CertEvent> The requested page of audit log entries.
+ */
+#[ic_cdk::query]
+fn get_tx_log(start: u64, limit: u64) -> Vec<CertEvent> {
+    STATE.with(|state| {
+        state.borrow().as_ref().map_or(vec![], |contract| {
+            contract.get_tx_log(start, limit)
+        })
+    })
+}
+
+
+/**
+ * @dev Retrieves every audit log entry that mentions `token_id`, letting a verifier
+ * reconstruct who minted/transferred/revoked a given certification and when.
+ * @param token_id The ID of the token to look up.
+ * @return Vec<CertEvent> The audit log entries affecting `token_id`.
+ */
+#[ic_cdk::query]
+fn get_token_history(token_id: u128) -> Vec<CertEvent> {
+    STATE.with(|state| {
+        state.borrow().as_ref().map_or(vec![], |contract| {
+            contract.get_token_history(token_id)
+        })
+    })
+}
+
+
+/**
+ * @dev Checks whether a certification is currently valid: it must be non-expiring or not
+ * yet have reached its expiry.
+ * @param token_id The ID of the token to check.
+ * @return bool True if the certification is currently valid.
+ */
+#[ic_cdk::query]
+fn is_valid(token_id: u128) -> bool {
+    STATE.with(|state| {
+        state
+            .borrow()
+            .as_ref()
+            .is_some_and(|contract| contract.is_valid(token_id as TokenId))
+    })
+}
+
+
+/**
+ * @dev Retrieves a certification's expiry timestamp, if it has one.
+ * @param token_id The ID of the token to look up.
+ * @return Option<u64> The expiry, in nanoseconds since epoch, or None if it never expires.
+ */
+#[ic_cdk::query]
+fn expiry_of(token_id: u128) -> Option<u64> {
+    STATE.with(|state| {
+        state
+            .borrow()
+            .as_ref()
+            .and_then(|contract| contract.expiry_of(token_id as TokenId))
+    })
+}
+
+
+/**
+ * @dev Replaces a certification's expiry. Callable by managers only.
+ * @param token_id The ID of the token to renew.
+ * @param new_expiry The new expiry, in nanoseconds since epoch.
+ * @return Result<(), String> Returns Ok if successful, or an error message.
+ */
+#[ic_cdk::update]
+fn renew(token_id: u128, new_expiry: u64) -> Result<(), String> {
+    STATE.with(|state| {
+        if let Some(contract) = state.borrow_mut().as_mut() {
+            contract.renew(token_id as TokenId, new_expiry)
+        } else {
+            Err("Contract not initialized".to_string())
+        }
+    })
+}
+
+
+/**
+ * @dev Sets the ICP amount `mint_paid` requires per mint. Only the contract owner may call
+ * this, and must additionally present a signature over their most recent `request_auth_nonce`
+ * nonce made with the private key matching a client certificate registered via
+ * `register_client_cert`.
+ * @param fee The new required payment amount.
+ * @param signature The signature proving possession of the registered client certificate's private key.
+ * @return Result<(), String> Returns Ok if successful, or an error message.
+ */
+#[ic_cdk::update]
+fn set_mint_fee(fee: Tokens, signature: Vec<u8>) -> Result<(), String> {
+    STATE.with(|state| {
+        if let Some(contract) = state.borrow_mut().as_mut() {
+            contract.set_mint_fee(fee, &signature)
+        } else {
+            Err("Contract not initialized".to_string())
+        }
+    })
+}
+
+
+/**
+ * @dev Sets the account `mint_paid` payments must be sent to. Only the contract owner may call
+ * this, and must additionally present a signature over their most recent `request_auth_nonce`
+ * nonce made with the private key matching a client certificate registered via
+ * `register_client_cert`.
+ * @param recipient The new fee recipient account identifier.
+ * @param signature The signature proving possession of the registered client certificate's private key.
+ * @return Result<(), String> Returns Ok if successful, or an error message.
+ */
+#[ic_cdk::update]
+fn set_fee_recipient(recipient: AccountIdentifier, signature: Vec<u8>) -> Result<(), String> {
+    STATE.with(|state| {
+        if let Some(contract) = state.borrow_mut().as_mut() {
+            contract.set_fee_recipient(recipient, &signature)
+        } else {
+            Err("Contract not initialized".to_string())
+        }
+    })
+}
+
+
+/**
+ * @dev Binds the SHA-256 fingerprint of a self-signed client certificate to the caller,
+ * layering a cryptographic second factor on top of principal-based access control. See
+ * `require_client_cert_auth`.
+ * @param der_cert The caller's DER-encoded client certificate.
+ * @return Result<(), String> Returns Ok if successful, or an error message.
+ */
+#[ic_cdk::update]
+fn register_client_cert(der_cert: Vec<u8>) -> Result<(), String> {
+    let caller = caller();
+    STATE.with(|state| {
+        if let Some(contract) = state.borrow_mut().as_mut() {
+            contract.register_client_cert(caller, &der_cert)
+        } else {
+            Err("Contract not initialized".to_string())
+        }
+    })
+}
+
+
+/**
+ * @dev Issues a fresh, per-caller auth nonce that a registered client certificate's private
+ * key must sign within `AUTH_NONCE_TTL_NANOS` to satisfy `require_client_cert_auth`,
+ * preventing a captured signature from being replayed indefinitely.
+ * @return Result<Vec<u8>, String> The nonce to sign, or an error message.
+ */
+#[ic_cdk::update]
+async fn request_auth_nonce() -> Result<Vec<u8>, String> {
+    let caller = caller();
+    let now = ic_cdk::api::time();
+    let (nonce,) = raw_rand()
+        .await
+        .map_err(|e| format!("Failed to fetch randomness: {:?}", e))?;
+
+    STATE.with(|state| {
+        if let Some(contract) = state.borrow_mut().as_mut() {
+            contract.issue_auth_nonce(caller, nonce.clone(), now);
+        }
+    });
+
+    Ok(nonce)
+}
+
+
+/**
+ * @dev Reads the current `mint_paid` payment requirements.
+ * @return (Tokens, AccountIdentifier) The required fee and the account it must be paid to.
+ */
+#[ic_cdk::query]
+fn get_mint_config() -> (Tokens, AccountIdentifier) {
+    STATE.with(|state| {
+        state.borrow().as_ref().map_or(
+            (Tokens::from_e8s(0), AccountIdentifier::new(&ic_cdk::id(), &DEFAULT_SUBACCOUNT)),
+            |contract| (contract.mint_fee, contract.fee_recipient),
+        )
+    })
+}
+
+
+/// Follows a `query_blocks` archive pointer to fetch `block_index` once the ledger has
+/// archived it — the routine case for any block index that isn't recent.
+async fn resolve_archived_block(
+    archived_blocks: &[ArchivedBlocksRange],
+    block_index: BlockIndex,
+) -> Result<Block, String> {
+    let archive = archived_blocks
+        .iter()
+        .find(|range| block_index >= range.start && block_index < range.start + range.length)
+        .ok_or_else(|| "NonExistingBlock: ledger returned no block at that index".to_string())?;
+
+    let args = GetBlocksArgs { start: block_index, length: 1 };
+    let (block_range,): (BlockRange,) =
+        call(archive.callback.principal, &archive.callback.method, (args,))
+            .await
+            .map_err(|e| format!("Failed to query archive canister: {:?}", e))?;
+
+    block_range
+        .blocks
+        .into_iter()
+        .next()
+        .ok_or_else(|| "NonExistingBlock: archive canister returned no block at that index".to_string())
+}
+
+/// Looks up `block_index` on `ledger_id` (following the archive pointer if the ledger has
+/// already archived it) and confirms it is a transfer to `recipient` for at least `required_fee`.
+async fn verify_payment_block(
+    ledger_id: Principal,
+    block_index: BlockIndex,
+    required_fee: Tokens,
+    recipient: AccountIdentifier,
+) -> Result<(), String> {
+    let query_args = GetBlocksArgs { start: block_index, length: 1 };
+    let blocks_result: QueryBlocksResult = call(ledger_id, "query_blocks", (query_args,))
+        .await
+        .map_err(|e| format!("Failed to query ledger: {:?}", e))?
+        .0;
+
+    let block = match blocks_result.blocks.into_iter().next() {
+        Some(block) => block,
+        None => resolve_archived_block(&blocks_result.archived_blocks, block_index).await?,
+    };
+
+    let operation = block
+        .transaction
+        .operation
+        .ok_or_else(|| "InvalidBlock: block has no operation".to_string())?;
+
+    match operation {
+        Operation::Transfer { to, amount, .. } => {
+            if to != recipient {
+                return Err("WrongRecipient: payment was not sent to this contract's fee recipient".to_string());
+            }
+            if amount < required_fee {
+                return Err("InsufficientPayment".to_string());
+            }
+            Ok(())
+        }
+        _ => Err("InvalidBlock: block is not a transfer".to_string()),
+    }
+}
+
+/**
+ * @dev Mints a certification gated by an ICP payment. Looks up `block_index` on `ledger_id`,
+ * confirms it is a transfer to this contract's fee recipient for at least `mint_fee`, and
+ * guards against replay before minting via `call_icrc7_mint_unchecked` (the verified payment
+ * stands in for the manager check).
+ * @param canister_id The Principal of the NFT canister to mint on.
+ * @param ledger_id The Principal of the ICP ledger canister to verify the payment against.
+ * @param block_index The ledger block index the payment was recorded in.
+ * @param args The arguments containing the details of the NFT to mint.
+ * @return Result<u128, String> Returns the minted token ID or an error message.
+ */
+#[ic_cdk::update]
+async fn mint_paid(
+    canister_id: Principal,
+    ledger_id: Principal,
+    block_index: BlockIndex,
+    args: MintArgs,
+) -> Result<u128, String> {
+    // Reserve `block_index` synchronously, before any `.await`, so two concurrent calls for
+    // the same block can't both observe "not yet consumed" and mint twice off one payment.
+    let (already_consumed, required_fee, recipient) = STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let Some(contract) = state.as_mut() else {
+            return (
+                true,
+                Tokens::from_e8s(0),
+                AccountIdentifier::new(&ic_cdk::id(), &DEFAULT_SUBACCOUNT),
+            );
+        };
+        let already_consumed = contract.is_block_consumed(block_index);
+        if !already_consumed {
+            contract.mark_block_consumed(block_index);
+        }
+        (already_consumed, contract.mint_fee, contract.fee_recipient)
+    });
+
+    if already_consumed {
+        return Err("BlockAlreadyConsumed".to_string());
+    }
+
+    if let Err(err) = verify_payment_block(ledger_id, block_index, required_fee, recipient).await {
+        // The reservation only guards against replaying a *valid* payment; an invalid one
+        // shouldn't permanently block a legitimate retry at the same block index.
+        STATE.with(|state| {
+            if let Some(contract) = state.borrow_mut().as_mut() {
+                contract.unmark_block_consumed(block_index);
+            }
+        });
+        return Err(err);
+    }
+
+    let state_clone = STATE.with(|state| state.borrow().clone());
+    let Some(contract) = state_clone else {
+        return Err("Contract not initialized".to_string());
+    };
+
+    let owner = args.owner.owner;
+    let expires_at = args.expires_at;
+    // A verified ICP payment is this flow's authorization; the purchaser need not also be a
+    // manager, so mint without `call_icrc7_mint`'s manager check.
+    let token_id = match contract.call_icrc7_mint_unchecked(canister_id, args).await {
+        Ok(token_id) => token_id,
+        Err(err) => {
+            // The payment was real but minting failed (e.g. the NFT canister rejected it) —
+            // release the reservation so the caller can retry against the same block_index.
+            STATE.with(|state| {
+                if let Some(contract) = state.borrow_mut().as_mut() {
+                    contract.unmark_block_consumed(block_index);
+                }
+            });
+            return Err(err);
+        }
+    };
+
+    STATE.with(|state| {
+        if let Some(contract) = state.borrow_mut().as_mut() {
+            contract.log_event(CertEventKind::Mint, ic_cdk::caller(), vec![token_id], Some(owner));
+            contract.record_expiry(token_id as TokenId, expires_at);
+            contract.record_mint(token_id as TokenId, owner);
+        }
+    });
+
+    Ok(token_id)
+}
+
+
 /**
  * @dev Asynchronously calls the `mint` method on another canister to mint a new NFT.
  * @param canister_id The Principal of the canister to call.
@@ -609,10 +1768,11 @@ fn get_managers() -> Vec<Principal> {
  * @param name The name of the NFT.
  * @param description An optional description of the NFT.
  * @param logo An optional logo for the NFT.
+ * @param expires_at An optional expiry, in nanoseconds since epoch, after which the certification is no longer valid.
  * @return Result<u128, String> Returns the minted token ID or an error message.
  */
 #[ic_cdk::update]
-async fn mint(canister_id: Principal, owner: Principal, name: String, description: Option<String>, logo: Option<String>) -> Result<u128, String> {
+async fn mint(canister_id: Principal, owner: Principal, name: String, description: Option<String>, logo: Option<String>, expires_at: Option<u64>) -> Result<u128, String> {
     let account = Account {
         owner,
         subaccount: None,
@@ -622,17 +1782,28 @@ async fn mint(canister_id: Principal, owner: Principal, name: String, descriptio
         name,
         description,
         logo,
+        expires_at,
     };
 
     let state_clone = STATE.with(|state| {
         state.borrow().clone()
     });
 
-    if let Some(contract) = state_clone {
-        contract.call_icrc7_mint(canister_id, args).await       // Call the async mint method.
-    } else {
-        Err("Contract not initialized".to_string())
-    }
+    let Some(contract) = state_clone else {
+        return Err("Contract not initialized".to_string());
+    };
+
+    let token_id = contract.call_icrc7_mint(canister_id, args).await?;      // Call the async mint method.
+
+    STATE.with(|state| {
+        if let Some(contract) = state.borrow_mut().as_mut() {
+            contract.log_event(CertEventKind::Mint, ic_cdk::caller(), vec![token_id], Some(owner));
+            contract.record_expiry(token_id as TokenId, expires_at);
+            contract.record_mint(token_id as TokenId, owner);
+        }
+    });
+
+    Ok(token_id)
 }
 
 
@@ -645,27 +1816,43 @@ async fn mint(canister_id: Principal, owner: Principal, name: String, descriptio
  * @param names A vector of names for the NFTs.
  * @param descriptions A vector of optional descriptions for the NFTs.
  * @param logos A vector of optional logos for the NFTs.
+ * @param expires_at A vector of optional expiries, in nanoseconds since epoch, one per NFT.
  * @return Result<Vec<u128>, String> Returns a vector of minted token IDs or an error message.
  */
 #[ic_cdk::update]
-async fn mintBatch(canister_id: Principal, owners: Vec<Principal>, names: Vec<String>, descriptions: Vec<Option<String>>, logos: Vec<Option<String>>) -> Result<Vec<u128>, String> {
-    let accounts = owners.into_iter().map(|owner| Account { owner, subaccount: None }).collect();
+async fn mintBatch(canister_id: Principal, owners: Vec<Principal>, names: Vec<String>, descriptions: Vec<Option<String>>, logos: Vec<Option<String>>, expires_at: Vec<Option<u64>>) -> Result<Vec<u128>, String> {
+    let accounts = owners.iter().map(|&owner| Account { owner, subaccount: None }).collect();
     let args = MintBatchArgs {
         owners: accounts,
         names,
         descriptions,
         logos,
+        expires_at: expires_at.clone(),
     };
 
     let state_clone = STATE.with(|state| {
         state.borrow().clone()
     });
 
-    if let Some(contract) = state_clone {
-        contract.call_icrc7_mint_batch(canister_id, args).await     // Call the async mint_batch method.
-    } else {
-        Err("Contract not initialized".to_string())
-    }
+    let Some(contract) = state_clone else {
+        return Err("Contract not initialized".to_string());
+    };
+
+    let token_ids = contract.call_icrc7_mint_batch(canister_id, args).await?;    // Call the async mint_batch method.
+
+    STATE.with(|state| {
+        if let Some(contract) = state.borrow_mut().as_mut() {
+            contract.log_event(CertEventKind::MintBatch, ic_cdk::caller(), token_ids.clone(), None);
+            for ((&token_id, &token_expires_at), &owner) in
+                token_ids.iter().zip(expires_at.iter()).zip(owners.iter())
+            {
+                contract.record_expiry(token_id as TokenId, token_expires_at);
+                contract.record_mint(token_id as TokenId, owner);
+            }
+        }
+    });
+
+    Ok(token_ids)
 }
 
 
@@ -692,7 +1879,7 @@ async fn transfer(
         subaccount: from_subaccount,
     };
 
-    let transfer_args: Vec<TransferArg> = token_ids.into_iter().map(|token_id| TransferArg {
+    let transfer_args: Vec<TransferArg> = token_ids.iter().map(|&token_id| TransferArg {
         token_id,
         from_subaccount,
         to: Account {
@@ -705,13 +1892,460 @@ async fn transfer(
 
     let state_clone = STATE.with(|state| state.borrow().clone());
 
-    if let Some(contract) = state_clone {
-        contract.call_icrc7_transfer(canister_id, account, transfer_args).await     // Call the async transfer method.
-    } else {
-        Err("Contract not initialized".to_string())
+    let Some(contract) = state_clone else {
+        return Err("Contract not initialized".to_string());
+    };
+
+    let results = contract.call_icrc7_transfer(canister_id, account, transfer_args).await?;     // Call the async transfer method.
+
+    STATE.with(|state| {
+        if let Some(contract) = state.borrow_mut().as_mut() {
+            let mut transferred = Vec::new();
+            for result in &results {
+                if let Ok(token_id) = result {
+                    contract.record_transfer(*token_id as TokenId, to_principal);
+                    transferred.push(*token_id);
+                }
+            }
+            contract.log_event(CertEventKind::Transfer, caller, transferred, Some(to_principal));
+        }
+    });
+
+    Ok(results)
+}
+
+
+/**
+ * @dev Revokes a certification by burning it on the NFT canister and recording why.
+ * Callable by any manager, following the DIP-721 three-level model.
+ * @param canister_id The Principal of the canister to call.
+ * @param token_id The ID of the token to revoke.
+ * @param from_subaccount The subaccount of the current owner the token is burned from.
+ * @param reason An optional human-readable reason for the revocation.
+ * @return Result<u128, String> Returns the burned token ID or an error message.
+ */
+#[ic_cdk::update]
+async fn revoke_certification(
+    canister_id: Principal,
+    token_id: u128,
+    from_subaccount: Option<[u8; 32]>,
+    reason: Option<String>,
+) -> Result<u128, String> {
+    let caller_principal = ic_cdk::caller();
+    // Burn from the token's tracked owner; an untracked token is burned from the caller.
+    let token_owner = STATE.with(|state| {
+        state.borrow().as_ref().and_then(|contract| contract.owner_of(token_id as TokenId))
+    });
+    let account = Account {
+        owner: token_owner.unwrap_or(caller_principal),
+        subaccount: from_subaccount,
+    };
+    let args = vec![BurnArg {
+        token_id,
+        from_subaccount,
+        memo: None,
+    }];
+
+    let state_clone = STATE.with(|state| state.borrow().clone());
+
+    let Some(contract) = state_clone else {
+        return Err("Contract not initialized".to_string());
+    };
+
+    let results = contract.call_icrc7_burn(canister_id, account, args).await?;
+    let burned_id = results
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Burn returned no results".to_string())??;
+
+    STATE.with(|state| {
+        if let Some(contract) = state.borrow_mut().as_mut() {
+            contract.record_burn(burned_id as TokenId, caller_principal, reason);
+            contract.log_event(CertEventKind::Revoke, caller_principal, vec![burned_id], None);
+        }
+    });
+
+    Ok(burned_id)
+}
+
+
+/**
+ * @dev Restores a wrongly-revoked certification. Only the contract owner, acting as
+ * custodian, may un-burn a token; managers cannot. Since the NFT canister's burn is
+ * permanent for the original token id, the certification is re-minted for its last known
+ * owner under a new token id rather than reusing the burned one.
+ * @param canister_id The Principal of the canister to call.
+ * @param token_id The ID of the previously burned token to restore.
+ * @return Result<u128, String> Returns the newly minted token ID or an error message.
+ */
+#[ic_cdk::update]
+async fn unburn_certification(canister_id: Principal, token_id: u128) -> Result<u128, String> {
+    let caller = ic_cdk::caller();
+
+    let (is_custodian, record) = STATE.with(|state| {
+        state.borrow().as_ref().map_or((false, None), |contract| {
+            (
+                contract.owner == caller,
+                contract.burned.get(&(token_id as TokenId)).cloned(),
+            )
+        })
+    });
+
+    if !is_custodian {
+        return Err("UnauthorizedUnburn".to_string());
+    }
+    let Some(record) = record else {
+        return Err("NotBurned".to_string());
+    };
+
+    let state_clone = STATE.with(|state| state.borrow().clone());
+    let Some(contract) = state_clone else {
+        return Err("Contract not initialized".to_string());
+    };
+
+    let args = MintArgs {
+        owner: Account { owner: record.owner, subaccount: None },
+        name: String::new(),
+        description: None,
+        logo: None,
+        expires_at: contract.expiry_of(token_id as TokenId),
+    };
+    let new_token_id = contract.call_icrc7_mint(canister_id, args).await?;
+
+    STATE.with(|state| {
+        if let Some(contract) = state.borrow_mut().as_mut() {
+            contract.burned.remove(&(token_id as TokenId));
+            contract.record_mint(new_token_id as TokenId, record.owner);
+            contract.log_event(
+                CertEventKind::Unburn,
+                caller,
+                vec![token_id, new_token_id],
+                Some(record.owner),
+            );
+        }
+    });
+
+    Ok(new_token_id)
+}
+
+
+/**
+ * @dev Issues a C2PA-style signed provenance manifest binding `asset_hash` to `assertions`.
+ * The claim (asset hash plus the SHA-256 of every assertion) is signed with the canister's
+ * threshold-ECDSA key, producing a self-contained, tamper-evident certificate that can be
+ * checked with `verify_manifest` against the canister's cached public key.
+ * @param asset_hash The hash of the asset the manifest is attesting to.
+ * @param assertions The C2PA-style assertions (e.g. actions, hash bindings) to bundle.
+ * @return Result<Manifest, String> Returns the signed manifest or an error message.
+ */
+#[ic_cdk::update]
+async fn certify_with_manifest(asset_hash: Vec<u8>, assertions: Vec<Assertion>) -> Result<Manifest, String> {
+    let assertion_hashes: Vec<Vec<u8>> = assertions.iter().map(assertion_hash).collect();
+    let claim = Claim {
+        asset_hash,
+        assertion_hashes,
+    };
+
+    let claim_bytes = serde_cbor::to_vec(&claim).map_err(|e| format!("Failed to serialize claim: {:?}", e))?;
+    let claim_hash = sha256(&claim_bytes);
+
+    let cached_key = STATE.with(|state| {
+        state
+            .borrow()
+            .as_ref()
+            .and_then(|contract| contract.cached_ecdsa_public_key())
+    });
+
+    if cached_key.is_none() {
+        let (public_key_reply,) = ecdsa_public_key(EcdsaPublicKeyArgument {
+            canister_id: None,
+            derivation_path: vec![],
+            key_id: ecdsa_key_id(),
+        })
+        .await
+        .map_err(|e| format!("Failed to fetch ECDSA public key: {:?}", e))?;
+
+        STATE.with(|state| {
+            if let Some(contract) = state.borrow_mut().as_mut() {
+                contract.set_cached_ecdsa_public_key(public_key_reply.public_key);
+            }
+        });
+    }
+
+    let (signature_reply,) = sign_with_ecdsa(SignWithEcdsaArgument {
+        message_hash: claim_hash,
+        derivation_path: vec![],
+        key_id: ecdsa_key_id(),
+    })
+    .await
+    .map_err(|e| format!("Failed to sign claim: {:?}", e))?;
+
+    Ok(Manifest {
+        assertions,
+        claim,
+        claim_signature: CoseSign1 {
+            signature: signature_reply.signature,
+            signer: ic_cdk::id(),
+        },
+    })
+}
+
+
+/**
+ * @dev Verifies a provenance manifest entirely off the call path: re-hashes every assertion,
+ * checks them against the hashes recorded in the claim, re-hashes the claim, and validates
+ * the claim signature against the canister's cached threshold-ECDSA public key.
+ * @param manifest The manifest to verify.
+ * @return VerificationResult Whether the manifest is valid, or why it isn't.
+ */
+#[ic_cdk::query]
+fn verify_manifest(manifest: Manifest) -> VerificationResult {
+    if manifest.assertions.len() != manifest.claim.assertion_hashes.len() {
+        return VerificationResult::Invalid("AssertionCountMismatch".to_string());
+    }
+
+    for (assertion, expected_hash) in manifest.assertions.iter().zip(manifest.claim.assertion_hashes.iter()) {
+        if assertion_hash(assertion) != *expected_hash {
+            return VerificationResult::Invalid(format!("AssertionTampered: {}", assertion.label));
+        }
+    }
+
+    let claim_bytes = match serde_cbor::to_vec(&manifest.claim) {
+        Ok(bytes) => bytes,
+        Err(e) => return VerificationResult::Invalid(format!("Failed to serialize claim: {:?}", e)),
+    };
+    let claim_hash = sha256(&claim_bytes);
+
+    let cached_key = STATE.with(|state| {
+        state
+            .borrow()
+            .as_ref()
+            .and_then(|contract| contract.cached_ecdsa_public_key())
+    });
+
+    let Some(public_key) = cached_key else {
+        return VerificationResult::Invalid("PublicKeyUnavailable: no manifest has been signed yet".to_string());
+    };
+
+    let Ok(verifying_key) = VerifyingKey::from_sec1_bytes(&public_key) else {
+        return VerificationResult::Invalid("InvalidPublicKey".to_string());
+    };
+    let Ok(signature) = Signature::from_slice(&manifest.claim_signature.signature) else {
+        return VerificationResult::Invalid("InvalidSignatureEncoding".to_string());
+    };
+
+    match verifying_key.verify_prehash(&claim_hash, &signature) {
+        Ok(()) => VerificationResult::Valid,
+        Err(_) => VerificationResult::Invalid("SignatureMismatch".to_string()),
+    }
+}
+
+/**
+ * @dev Verifies a DER-encoded X.509 certificate against a caller-supplied set of DNS TLSA
+ * records (RFC 6698), letting the canister anchor trust in a self-signed certificate without
+ * a classic CA chain. Because the canister cannot resolve DNS itself, `tlsa_records` must be
+ * supplied by the caller; the canister does not fetch TLSA records on its own.
+ * @param der_cert The DER-encoded certificate presented by the external party.
+ * @param tlsa_records The candidate TLSA records to check the certificate against.
+ * @return true if at least one DANE-EE(3) or DANE-TA(2) record matches the certificate.
+ */
+#[ic_cdk::update]
+fn verify_cert_dane(der_cert: Vec<u8>, tlsa_records: Vec<TlsaRecord>) -> bool {
+    let Ok((_, cert)) = parse_x509_certificate(&der_cert) else {
+        return false;
+    };
+    let spki = cert.tbs_certificate.subject_pki.raw;
+
+    for record in &tlsa_records {
+        if record.usage != DANE_TA && record.usage != DANE_EE {
+            continue; // PKIX-TA(0)/PKIX-EE(1) would require a classic CA chain we don't validate.
+        }
+
+        let selected: &[u8] = match record.selector {
+            0 => &der_cert,
+            1 => spki,
+            _ => continue,
+        };
+
+        let matches = match record.matching_type {
+            0 => selected == record.association_data.as_slice(),
+            1 => sha256(selected) == record.association_data,
+            2 => sha512(selected) == record.association_data,
+            _ => continue,
+        };
+
+        if matches {
+            return true;
+        }
     }
+
+    false
+}
+
+
+ic_cdk::export_candid!();
+
+
+// --- Kani model checking: a bounded model of the certificate-issuing state machine ---
+//
+// Kani's bounded model checker can't reason about the real `CertificationNFT` directly — it
+// is driven by IC I/O (stable memory, inter-canister ECDSA/ledger calls, `thread_local!`
+// globals) that only make sense inside the replica. `CertState` below extracts just the
+// invariant-bearing shape of that state machine (init-once, monotonic issuance, no
+// un-revoking) into plain, bounded data Kani can exhaustively explore. Enabling this requires
+// `kani = "0.1"` under `[target.'cfg(kani)'.dependencies]` in Cargo.toml; run with
+// `cargo kani -Z function-contracts`.
+const MAX_CERTS: usize = 8;
+
+#[cfg_attr(kani, derive(kani::Arbitrary))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct CertState {
+    initialized: bool,
+    issued: [bool; MAX_CERTS],
+    revoked: [bool; MAX_CERTS],
 }
 
+impl CertState {
+    const fn new() -> Self {
+        Self {
+            initialized: false,
+            issued: [false; MAX_CERTS],
+            revoked: [false; MAX_CERTS],
+        }
+    }
 
+    fn is_initialized(&self) -> bool {
+        self.initialized
+    }
 
-ic_cdk::export_candid!();
\ No newline at end of file
+    fn issued_count(&self) -> usize {
+        self.issued.iter().filter(|&&is_issued| is_issued).count()
+    }
+
+    /// Initializes the state machine. Mirrors the `init()` canister entry point's
+    /// init-once invariant; at runtime the endpoints only guard it by hand, returning
+    /// "Contract not initialized" while `STATE` is `None`.
+    #[cfg_attr(kani, kani::requires(!self.is_initialized()))]
+    #[cfg_attr(kani, kani::ensures(|_| self.is_initialized()))]
+    fn init(&mut self) {
+        *self = Self::new();
+        self.initialized = true;
+    }
+
+    /// Issues certificate `id`. Mirrors the `mint` endpoint's local bookkeeping (`record_mint`).
+    #[cfg_attr(kani, kani::requires(self.is_initialized() && id < MAX_CERTS))]
+    #[cfg_attr(kani, kani::ensures(|_| self.issued_count() >= old(self.issued_count())))]
+    fn issue(&mut self, id: usize) {
+        self.issued[id] = true;
+    }
+
+    /// Revokes certificate `id`. Mirrors `revoke_certification`'s local bookkeeping.
+    /// A previously-revoked id can never become un-revoked by a later call.
+    #[cfg_attr(kani, kani::requires(self.is_initialized() && id < MAX_CERTS && self.issued[id]))]
+    #[cfg_attr(kani, kani::ensures(|_| self.revoked[id] && (!old(self.revoked[id]) || self.revoked[id])))]
+    fn revoke(&mut self, id: usize) {
+        self.revoked[id] = true;
+    }
+}
+
+#[cfg(kani)]
+mod kani_proofs {
+    use super::*;
+
+    #[kani::proof_for_contract(CertState::init)]
+    fn init_contract_proof() {
+        let mut state: CertState = kani::any();
+        state.init();
+    }
+
+    #[kani::proof_for_contract(CertState::issue)]
+    fn issue_contract_proof() {
+        let mut state: CertState = kani::any();
+        let id: usize = kani::any();
+        state.issue(id);
+    }
+
+    #[kani::proof_for_contract(CertState::revoke)]
+    fn revoke_contract_proof() {
+        let mut state: CertState = kani::any();
+        let id: usize = kani::any();
+        state.revoke(id);
+    }
+
+    /// Revoking an already-revoked id, or issuing it again, never un-revokes it.
+    #[kani::proof]
+    fn revoke_never_resurrects() {
+        let mut state: CertState = kani::any();
+        let id: usize = kani::any();
+        kani::assume(state.is_initialized());
+        kani::assume(id < MAX_CERTS);
+        kani::assume(state.issued[id]);
+        kani::assume(state.revoked[id]);
+
+        state.revoke(id);
+        assert!(state.revoked[id]);
+        state.issue(id);
+        assert!(state.revoked[id]);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_state_round_trips_through_candid() {
+        let mut contract = CertificationNFT {
+            owner: Principal::anonymous(),
+            ..Default::default()
+        };
+        contract.is_manager.insert(Principal::anonymous(), true);
+        contract.next_token_id = 7;
+        contract.burned.insert(
+            3,
+            BurnRecord {
+                revoker: Principal::anonymous(),
+                burned_at: 42,
+                reason: Some("fraud".to_string()),
+                owner: Principal::anonymous(),
+            },
+        );
+
+        let saved = StableState::V1(contract.clone());
+        let bytes = candid::encode_one(&saved).expect("encode stable state");
+        let restored: StableState = candid::decode_one(&bytes).expect("decode stable state");
+
+        let StableState::V1(restored_contract) = restored;
+        assert_eq!(restored_contract.owner, contract.owner);
+        assert_eq!(restored_contract.next_token_id, contract.next_token_id);
+        assert_eq!(restored_contract.is_manager, contract.is_manager);
+        assert_eq!(restored_contract.burned.len(), contract.burned.len());
+    }
+
+    /// Pins `export_candid()`'s output against the checked-in `.did` file so interface
+    /// drift fails the build instead of surfacing as a `dfx deploy` warning. Set
+    /// `UPDATE_DID=1` to regenerate the checked-in file from the current source.
+    #[test]
+    fn candid_interface_matches_checked_in_did_file() {
+        let generated = export_candid();
+        let did_path =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("genun_certification.did");
+
+        if std::env::var_os("UPDATE_DID").is_some() {
+            std::fs::write(&did_path, &generated).expect("write genun_certification.did");
+            return;
+        }
+
+        let checked_in = std::fs::read_to_string(&did_path).unwrap_or_else(|_| {
+            panic!(
+                "{} is missing; run with UPDATE_DID=1 to generate it",
+                did_path.display()
+            )
+        });
+        assert_eq!(
+            generated, checked_in,
+            "genun_certification.did is stale; rerun with UPDATE_DID=1 to regenerate it"
+        );
+    }
+}
\ No newline at end of file
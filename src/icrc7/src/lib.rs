@@ -14,7 +14,12 @@ use icrc_nft_types::{
     Account,
 };
 use serde::Deserialize;
-use state::{get_txn_id, SetBaseUriArgs, Token, COLLECTION_METADATA, TOKEN_MAP};
+use state::{
+    append_block, clear_token_approvals, find_recent_txn, get_blocks, hash_transfer,
+    is_approved_spender, is_minter, is_minting_authority, prune_recent_txns, record_recent_txn,
+    ApprovalInfo, Block, CollectionApprovalKey, SetBaseUriArgs, Token, TokenApprovalKey,
+    COLLECTION_APPROVALS, COLLECTION_METADATA, TOKEN_APPROVALS, TOKEN_MAP,
+};
 
 #[derive(CandidType, Deserialize, Debug)]
 pub struct InitArg {
@@ -217,6 +222,38 @@ pub fn icrc7_token_metadata(token_ids: Vec<u128>) -> Vec<Option<Icrc7TokenMetada
     })
 }
 
+/// Attaches holder-only metadata (e.g. certificate details) to a token. Restricted to the
+/// current owner; excluded from `icrc7_token_metadata`.
+#[update]
+pub fn set_private_metadata(token_id: u128, metadata: Icrc7TokenMetadata) -> Result<(), String> {
+    let caller = ic_cdk::caller();
+    TOKEN_MAP.with_borrow_mut(|token_map| {
+        let Some(mut token) = token_map.get(&token_id) else {
+            return Err("NonExistingTokenId: The specified token does not exist.".to_string());
+        };
+        if token.owner.owner != caller {
+            return Err("Unauthorized".to_string());
+        }
+        token.sealed_metadata = Some(metadata);
+        token_map.insert(token_id, token);
+        Ok(())
+    })
+}
+
+#[query]
+pub fn get_private_metadata(token_id: u128) -> Result<Icrc7TokenMetadata, String> {
+    let caller = ic_cdk::caller();
+    query_token_map(|token_map| {
+        let token = token_map
+            .get(&token_id)
+            .ok_or_else(|| "NonExistingTokenId: The specified token does not exist.".to_string())?;
+        if token.owner.owner != caller {
+            return Err("Unauthorized".to_string());
+        }
+        token.sealed_metadata.clone().ok_or_else(|| "NotFound: No private metadata set for this token.".to_string())
+    })
+}
+
 #[query]
 pub fn icrc7_owner_of(token_ids: Vec<u128>) -> Vec<Option<Account>> {
     query_token_map(|token_map| {
@@ -251,12 +288,38 @@ pub fn icrc7_balance_of(accounts: Vec<Account>) -> Vec<Nat> {
     })
 }
 
+fn resolve_take(take: Option<u128>) -> u128 {
+    let take = take.unwrap_or_else(|| {
+        query_metadata(|metadata| metadata.icrc7_default_take_value).unwrap_or(u128::MAX)
+    });
+    let max_take = query_metadata(|metadata| metadata.icrc7_max_take_value).unwrap_or(u128::MAX);
+    take.min(max_take)
+}
+
+#[query]
 pub fn icrc7_tokens(prev: Option<u128>, take: Option<u128>) -> Vec<u128> {
-    todo!()
+    let take = resolve_take(take);
+    query_token_map(|token_map| {
+        token_map
+            .iter()
+            .map(|(id, _)| id)
+            .filter(|id| prev.map_or(true, |prev| *id > prev))
+            .take(take as usize)
+            .collect()
+    })
 }
 
+#[query]
 pub fn icrc7_tokens_of(account: Account, prev: Option<u128>, take: Option<u128>) -> Vec<u128> {
-    todo!()
+    let take = resolve_take(take);
+    query_token_map(|token_map| {
+        token_map
+            .iter()
+            .filter(|(id, token)| token.owner == account && prev.map_or(true, |prev| *id > prev))
+            .map(|(id, _)| id)
+            .take(take as usize)
+            .collect()
+    })
 }
 
 #[query]
@@ -330,6 +393,17 @@ pub struct MintArgs {
 
 #[update]
 pub fn mint(args: MintArgs) -> Result<u128, String> {
+    if !is_minter(ic_cdk::caller()) {
+        return Err("Unauthorized: Only the minting authority or a delegated minter can mint.".into());
+    }
+
+    let supply_cap = query_metadata(|metadata| metadata.icrc7_supply_cap);
+    if let Some(supply_cap) = supply_cap {
+        if icrc7_total_supply() >= supply_cap {
+            return Err("SupplyCapReached: Minting would exceed icrc7_supply_cap.".into());
+        }
+    }
+
     let MintArgs {
         owner,
         name,
@@ -350,6 +424,7 @@ pub fn mint(args: MintArgs) -> Result<u128, String> {
     });
 
     if success {
+        append_block("7mint", new_id, None, Some(owner), None);
         Ok(new_id)
     } else {
         Err("Failed to insert token".into())
@@ -414,20 +489,89 @@ pub fn mint_batch(args: MintBatchArgs) -> Result<Vec<u128>, String> {
     Ok(token_ids)
 }
 
+#[update]
+pub fn add_minter(minter: Principal) -> Result<(), String> {
+    if !is_minting_authority(ic_cdk::caller()) {
+        return Err("Unauthorized: Only the minting authority can add minters.".into());
+    }
+    state::add_minter(minter);
+    Ok(())
+}
+
+#[update]
+pub fn remove_minter(minter: Principal) -> Result<(), String> {
+    if !is_minting_authority(ic_cdk::caller()) {
+        return Err("Unauthorized: Only the minting authority can remove minters.".into());
+    }
+    state::remove_minter(minter);
+    Ok(())
+}
+
+#[query]
+pub fn list_minters() -> Vec<Principal> {
+    state::list_minters()
+}
+
 #[update]
 pub fn icrc7_transfer(caller: Account, args: Vec<TransferArg>) -> Vec<Result<u128, String>> {
+    if let Err(err) = assert_authorized_caller(&caller) {
+        return args.iter().map(|_| Err(err.clone())).collect();
+    }
+
+    let (max_memo_size, tx_window, permitted_drift) = query_metadata(|metadata| {
+        (
+            metadata.icrc7_max_memo_size,
+            metadata.icrc7_tx_window.unwrap_or(0) as u64,
+            metadata.icrc7_permitted_drift.unwrap_or(0) as u64,
+        )
+    });
+    let now = ic_cdk::api::time();
+    prune_recent_txns(now, tx_window + permitted_drift);
+
     let mut results = Vec::new();
 
     for arg in args {
+        // Step 0: Reject oversized memos, stale/future-dated requests, and exact retries of
+        // a transfer already applied within the dedup window.
+        if let Some(max_memo_size) = max_memo_size {
+            if arg.memo.as_ref().is_some_and(|memo| memo.len() as u128 > max_memo_size) {
+                results.push(Err("GenericError: memo exceeds icrc7_max_memo_size".to_string()));
+                continue;
+            }
+        }
+
+        let mut dedup_key = None;
+        if let Some(created_at_time) = arg.created_at_time {
+            if created_at_time < now.saturating_sub(tx_window + permitted_drift) {
+                results.push(Err("TooOld: created_at_time is outside the permitted tx_window".to_string()));
+                continue;
+            }
+            if created_at_time > now + permitted_drift {
+                results.push(Err("CreatedInFuture: created_at_time is ahead of canister time".to_string()));
+                continue;
+            }
+
+            let hash = hash_transfer(&caller.owner, arg.token_id, &arg.to, &arg.memo, created_at_time);
+            if let Some(recent) = find_recent_txn(&hash) {
+                results.push(Err(format!("Duplicate: duplicate_of={}", recent.txn_id)));
+                continue;
+            }
+            dedup_key = Some((hash, created_at_time));
+        }
+
         // Step 1: Retrieve the token and clone it for processing
         let token_opt =
             query_token_map(|token_map| token_map.get(&arg.token_id).map(|token| token.clone()));
 
         if let Some(mut token) = token_opt {
-            // Step 2: Ensure that the caller owns the token and the subaccount matches
-            if token.owner.owner != caller.owner || token.owner.subaccount != arg.from_subaccount {
+            // Step 2: Ensure that the caller owns the token (with matching subaccount) or is
+            // an ICRC-37 approved spender for it.
+            let is_owner =
+                token.owner.owner == caller.owner && token.owner.subaccount == arg.from_subaccount;
+            if !is_owner && !is_approved_spender(&token, caller.owner, ic_cdk::api::time()) {
                 results.push(Err(
-                    "Unauthorized: Only the token owner can transfer the token.".to_string(),
+                    "Unauthorized: Only the token owner or an approved spender can transfer the token."
+                        .to_string(),
                 ));
                 continue;
             }
@@ -441,13 +585,26 @@ pub fn icrc7_transfer(caller: Account, args: Vec<TransferArg>) -> Vec<Result<u12
             }
 
             // Step 4: Perform the transfer
+            let from = token.owner.clone();
             token.transfer(arg.to.clone());
 
-            // Step 5: Insert the updated token back into the map
+            // Step 5: Insert the updated token back into the map and clear stale approvals
             let token_id = token.id;
             TOKEN_MAP.with_borrow_mut(|map| {
                 map.insert(token_id, token);
             });
+            clear_token_approvals(token_id);
+
+            let txn_id = append_block(
+                "7xfer",
+                token_id,
+                Some(from),
+                Some(arg.to.clone()),
+                arg.memo.clone(),
+            );
+            if let Some((hash, created_at_time)) = dedup_key {
+                record_recent_txn(hash, txn_id, created_at_time);
+            }
 
             results.push(Ok(token_id));
         } else {
@@ -460,7 +617,273 @@ pub fn icrc7_transfer(caller: Account, args: Vec<TransferArg>) -> Vec<Result<u12
     results
 }
 
-pub fn burn() {}
+#[derive(CandidType, Deserialize, Debug)]
+pub struct BurnArg {
+    pub token_id: u128,
+    pub from_subaccount: Option<[u8; 32]>,
+    pub memo: Option<Vec<u8>>,
+}
+
+/// Verifies the immediate IC caller is actually entitled to act as `caller`: either `caller`
+/// itself (an end user calling directly) or the single configured minting authority acting as a
+/// controller canister on a user's behalf. Ordinary delegated minters (`is_minter`/`MINTERS`,
+/// added purely to authorize minting) are deliberately *not* accepted here — that would let
+/// anyone added for mint-only delegation also burn, transfer, or approve other users' tokens.
+fn assert_authorized_caller(caller: &Account) -> Result<(), String> {
+    let immediate_caller = ic_cdk::caller();
+    if caller.owner == immediate_caller || is_minting_authority(immediate_caller) {
+        Ok(())
+    } else {
+        Err("Unauthorized: the calling principal does not match the supplied account.".to_string())
+    }
+}
+
+/// Burns a token if `caller` is its owner or an approved spender. `get_next_token_id` only
+/// ever increments, so a burned id is never reissued even though it is removed here.
+#[update]
+pub fn icrc7_burn(caller: Account, args: Vec<BurnArg>) -> Vec<Result<u128, String>> {
+    if let Err(err) = assert_authorized_caller(&caller) {
+        return args.iter().map(|_| Err(err.clone())).collect();
+    }
+
+    args.into_iter()
+        .map(|arg| {
+            let token_opt =
+                query_token_map(|token_map| token_map.get(&arg.token_id).map(|token| token.clone()));
+
+            let Some(token) = token_opt else {
+                return Err("NonExistingTokenId: The specified token does not exist.".to_string());
+            };
+
+            let is_owner = token.owner.owner == caller.owner
+                && token.owner.subaccount == arg.from_subaccount;
+            if !is_owner && !is_approved_spender(&token, caller.owner, ic_cdk::api::time()) {
+                return Err(
+                    "Unauthorized: Only the token owner or an approved spender can burn the token."
+                        .to_string(),
+                );
+            }
+
+            TOKEN_MAP.with_borrow_mut(|map| {
+                map.remove(&arg.token_id);
+            });
+            clear_token_approvals(arg.token_id);
+            append_block("7burn", arg.token_id, Some(token.owner), None, arg.memo);
+
+            Ok(arg.token_id)
+        })
+        .collect()
+}
+
+// --- ICRC-37: approvals on top of ICRC-7 transfers ---
+
+#[derive(CandidType, Deserialize, Debug)]
+pub struct ApproveTokenArg {
+    pub token_id: u128,
+    pub spender: Account,
+    pub expires_at: Option<u64>,
+    pub memo: Option<Vec<u8>>,
+}
+
+#[derive(CandidType, Deserialize, Debug)]
+pub struct ApproveCollectionArg {
+    pub spender: Account,
+    pub expires_at: Option<u64>,
+    pub memo: Option<Vec<u8>>,
+}
+
+#[derive(CandidType, Deserialize, Debug)]
+pub struct RevokeTokenApprovalArg {
+    pub token_id: u128,
+    pub spender: Account,
+}
+
+#[update]
+pub fn icrc37_approve_tokens(
+    caller: Account,
+    args: Vec<ApproveTokenArg>,
+) -> Vec<Result<(), String>> {
+    if let Err(err) = assert_authorized_caller(&caller) {
+        return args.iter().map(|_| Err(err.clone())).collect();
+    }
+
+    args.into_iter()
+        .map(|arg| {
+            let owner = query_token_map(|token_map| token_map.get(&arg.token_id).map(|t| t.owner));
+            match owner {
+                Some(owner) if owner == caller => {
+                    TOKEN_APPROVALS.with_borrow_mut(|approvals| {
+                        approvals.insert(
+                            TokenApprovalKey {
+                                token_id: arg.token_id,
+                                spender: arg.spender.owner,
+                            },
+                            ApprovalInfo {
+                                expires_at: arg.expires_at,
+                                memo: arg.memo,
+                            },
+                        );
+                    });
+                    Ok(())
+                }
+                Some(_) => Err("Unauthorized: Only the token owner can approve a spender for it."
+                    .to_string()),
+                None => Err("NonExistingTokenId: The specified token does not exist.".to_string()),
+            }
+        })
+        .collect()
+}
+
+#[update]
+pub fn icrc37_approve_collection(
+    caller: Account,
+    args: Vec<ApproveCollectionArg>,
+) -> Vec<Result<(), String>> {
+    if let Err(err) = assert_authorized_caller(&caller) {
+        return args.iter().map(|_| Err(err.clone())).collect();
+    }
+
+    args.into_iter()
+        .map(|arg| {
+            COLLECTION_APPROVALS.with_borrow_mut(|approvals| {
+                approvals.insert(
+                    CollectionApprovalKey {
+                        owner: caller.owner,
+                        spender: arg.spender.owner,
+                    },
+                    ApprovalInfo {
+                        expires_at: arg.expires_at,
+                        memo: arg.memo,
+                    },
+                );
+            });
+            Ok(())
+        })
+        .collect()
+}
+
+#[update]
+pub fn icrc37_revoke_token_approvals(
+    caller: Account,
+    args: Vec<RevokeTokenApprovalArg>,
+) -> Vec<Result<(), String>> {
+    if let Err(err) = assert_authorized_caller(&caller) {
+        return args.iter().map(|_| Err(err.clone())).collect();
+    }
+
+    args.into_iter()
+        .map(|arg| {
+            let owner = query_token_map(|token_map| token_map.get(&arg.token_id).map(|t| t.owner));
+            match owner {
+                Some(owner) if owner == caller => {
+                    TOKEN_APPROVALS.with_borrow_mut(|approvals| {
+                        approvals.remove(&TokenApprovalKey {
+                            token_id: arg.token_id,
+                            spender: arg.spender.owner,
+                        });
+                    });
+                    Ok(())
+                }
+                Some(_) => Err("Unauthorized: Only the token owner can revoke an approval on it."
+                    .to_string()),
+                None => Err("NonExistingTokenId: The specified token does not exist.".to_string()),
+            }
+        })
+        .collect()
+}
+
+#[update]
+pub fn icrc37_revoke_collection_approvals(
+    caller: Account,
+    spenders: Vec<Account>,
+) -> Vec<Result<(), String>> {
+    if let Err(err) = assert_authorized_caller(&caller) {
+        return spenders.iter().map(|_| Err(err.clone())).collect();
+    }
+
+    spenders
+        .into_iter()
+        .map(|spender| {
+            COLLECTION_APPROVALS.with_borrow_mut(|approvals| {
+                approvals.remove(&CollectionApprovalKey {
+                    owner: caller.owner,
+                    spender: spender.owner,
+                });
+            });
+            Ok(())
+        })
+        .collect()
+}
+
+#[query]
+pub fn icrc37_get_token_approvals(token_id: u128) -> Vec<(Principal, ApprovalInfo)> {
+    TOKEN_APPROVALS.with_borrow(|approvals| {
+        approvals
+            .iter()
+            .filter(|(key, _)| key.token_id == token_id)
+            .map(|(key, info)| (key.spender, info))
+            .collect()
+    })
+}
+
+#[query]
+pub fn icrc37_get_collection_approvals(owner: Account) -> Vec<(Principal, ApprovalInfo)> {
+    COLLECTION_APPROVALS.with_borrow(|approvals| {
+        approvals
+            .iter()
+            .filter(|(key, _)| key.owner == owner.owner)
+            .map(|(key, info)| (key.spender, info))
+            .collect()
+    })
+}
+
+// --- ICRC-3: hash-chained block log ---
+
+#[derive(CandidType, Deserialize, Debug)]
+pub struct GetBlocksArg {
+    pub start: u64,
+    pub length: u64,
+}
+
+#[derive(CandidType, Debug)]
+pub struct GetBlocksResult {
+    pub blocks: Vec<Block>,
+    pub log_length: u64,
+}
+
+#[query]
+pub fn icrc3_get_blocks(args: Vec<GetBlocksArg>) -> GetBlocksResult {
+    let mut blocks = Vec::new();
+    let mut log_length = 0;
+    for arg in args {
+        let (mut page, length) = get_blocks(arg.start, arg.length);
+        log_length = length;
+        blocks.append(&mut page);
+    }
+    GetBlocksResult { blocks, log_length }
+}
+
+#[query]
+pub fn icrc3_get_tip_certificate() -> Option<Vec<u8>> {
+    ic_cdk::api::data_certificate()
+}
+
+#[derive(CandidType, Debug)]
+pub struct BlockType {
+    block_type: String,
+    url: String,
+}
+
+#[query]
+pub fn icrc3_supported_block_types() -> Vec<BlockType> {
+    vec!["7mint", "7xfer", "7burn"]
+        .into_iter()
+        .map(|block_type| BlockType {
+            block_type: block_type.into(),
+            url: "https://github.com/dfinity/ICRC/ICRCs/ICRC-3".into(),
+        })
+        .collect()
+}
 
 #[derive(CandidType, Debug)]
 pub struct Standard {
@@ -471,10 +894,18 @@ pub struct Standard {
 #[query]
 pub fn icrc10_supported_standards() -> Vec<Standard> {
     vec![
+        Standard {
+            name: "ICRC-3".into(),
+            url: "https://github.com/dfinity/ICRC/ICRCs/ICRC-3".into(),
+        },
         Standard {
             name: "ICRC-7".into(),
             url: "https://github.com/dfinity/ICRC/ICRCs/ICRC-7".into(),
         },
+        Standard {
+            name: "ICRC-37".into(),
+            url: "https://github.com/dfinity/ICRC/ICRCs/ICRC-37".into(),
+        },
         Standard {
             name: "ICRC-61".into(),
             url: "https://github.com/dfinity/ICRC/ICRCs/ICRC-61".into(),
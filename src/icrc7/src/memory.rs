@@ -0,0 +1,51 @@
+use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
+use ic_stable_structures::DefaultMemoryImpl;
+use std::cell::RefCell;
+
+pub type Memory = VirtualMemory<DefaultMemoryImpl>;
+
+const COLLECTION_METADATA_MEM_ID: MemoryId = MemoryId::new(0);
+const TOKEN_MAP_MEM_ID: MemoryId = MemoryId::new(1);
+const TOKEN_APPROVALS_MEM_ID: MemoryId = MemoryId::new(2);
+const COLLECTION_APPROVALS_MEM_ID: MemoryId = MemoryId::new(3);
+const RECENT_TXNS_MEM_ID: MemoryId = MemoryId::new(4);
+const BLOCK_LOG_INDEX_MEM_ID: MemoryId = MemoryId::new(5);
+const BLOCK_LOG_DATA_MEM_ID: MemoryId = MemoryId::new(6);
+const MINTERS_MEM_ID: MemoryId = MemoryId::new(7);
+
+thread_local! {
+    static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> =
+        RefCell::new(MemoryManager::init(DefaultMemoryImpl::default()));
+}
+
+pub fn get_collection_metadata_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(COLLECTION_METADATA_MEM_ID))
+}
+
+pub fn get_token_map_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(TOKEN_MAP_MEM_ID))
+}
+
+pub fn get_token_approvals_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(TOKEN_APPROVALS_MEM_ID))
+}
+
+pub fn get_collection_approvals_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(COLLECTION_APPROVALS_MEM_ID))
+}
+
+pub fn get_recent_txns_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(RECENT_TXNS_MEM_ID))
+}
+
+pub fn get_block_log_index_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(BLOCK_LOG_INDEX_MEM_ID))
+}
+
+pub fn get_block_log_data_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(BLOCK_LOG_DATA_MEM_ID))
+}
+
+pub fn get_minters_memory() -> Memory {
+    MEMORY_MANAGER.with(|m| m.borrow().get(MINTERS_MEM_ID))
+}
@@ -0,0 +1,460 @@
+use crate::memory::{
+    get_block_log_data_memory, get_block_log_index_memory, get_collection_approvals_memory,
+    get_collection_metadata_memory, get_minters_memory, get_recent_txns_memory,
+    get_token_approvals_memory, get_token_map_memory, Memory,
+};
+use crate::utils::now;
+use candid::{CandidType, Decode, Encode, Principal};
+use icrc_nft_types::{icrc7::metadata::Icrc7TokenMetadata, Account};
+use ic_stable_structures::{log::StableLog, storable::Bound, StableBTreeMap, StableCell, Storable};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::cell::RefCell;
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct CollectionMetadata {
+    pub minting_auth: Option<Account>,
+    pub icrc7_name: Option<String>,
+    pub icrc7_symbol: Option<String>,
+    pub icrc7_description: Option<String>,
+    pub icrc7_logo: Option<String>,
+    pub icrc7_supply_cap: Option<u128>,
+    pub icrc7_max_query_batch_size: Option<u128>,
+    pub icrc7_max_update_batch_size: Option<u128>,
+    pub icrc7_default_take_value: Option<u128>,
+    pub icrc7_max_take_value: Option<u128>,
+    pub icrc7_max_memo_size: Option<u128>,
+    pub icrc7_atomic_batch_transfer: Option<bool>,
+    pub icrc7_tx_window: Option<u128>,
+    pub icrc7_permitted_drift: Option<u128>,
+    pub base_token_uri: String,
+    pub next_token_id: u128,
+}
+
+impl Storable for CollectionMetadata {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(CandidType, Deserialize, Debug)]
+pub struct SetBaseUriArgs {
+    pub uri: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Token {
+    pub id: u128,
+    pub owner: Account,
+    pub name: String,
+    pub logo: Option<String>,
+    pub description: Option<String>,
+    /// Holder-only metadata (e.g. certificate details), excluded from `token_metadata()`
+    /// and only ever handed back to the owner via `get_private_metadata`.
+    pub sealed_metadata: Option<Icrc7TokenMetadata>,
+}
+
+impl Token {
+    pub fn new(
+        id: u128,
+        owner: Account,
+        name: String,
+        logo: Option<String>,
+        description: Option<String>,
+    ) -> Self {
+        Self {
+            id,
+            owner,
+            name,
+            logo,
+            description,
+            sealed_metadata: None,
+        }
+    }
+
+    pub fn token_metadata(&self) -> Icrc7TokenMetadata {
+        use icrc_ledger_types::icrc::generic_metadata_value::MetadataValue;
+
+        let mut map = Icrc7TokenMetadata::new();
+        map.insert("icrc7:name".into(), MetadataValue::Text(self.name.clone()));
+        if let Some(logo) = &self.logo {
+            map.insert("icrc7:logo".into(), MetadataValue::Text(logo.clone()));
+        }
+        if let Some(description) = &self.description {
+            map.insert(
+                "icrc7:description".into(),
+                MetadataValue::Text(description.clone()),
+            );
+        }
+        map
+    }
+
+    pub fn transfer(&mut self, to: Account) {
+        self.owner = to;
+    }
+}
+
+impl Storable for Token {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Key for a token-level ICRC-37 approval. Approvals are tracked per spender principal;
+/// the spender's subaccount (if any) is not part of the key, so `is_approved_spender`
+/// matches a spender by principal alone.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TokenApprovalKey {
+    pub token_id: u128,
+    pub spender: Principal,
+}
+
+impl Storable for TokenApprovalKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Key for a collection-level ("approve-all") ICRC-37 approval.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CollectionApprovalKey {
+    pub owner: Principal,
+    pub spender: Principal,
+}
+
+impl Storable for CollectionApprovalKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ApprovalInfo {
+    pub expires_at: Option<u64>,
+    pub memo: Option<Vec<u8>>,
+}
+
+impl ApprovalInfo {
+    pub fn is_active(&self, now: u64) -> bool {
+        self.expires_at.map_or(true, |expires_at| expires_at > now)
+    }
+}
+
+impl Storable for ApprovalInfo {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// SHA-256 digest of `(caller, token_id, to, memo, created_at_time)`, used to recognize a
+/// retried `icrc7_transfer` request within the permitted dedup window.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TxnHash(pub [u8; 32]);
+
+impl Storable for TxnHash {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(self.0.to_vec())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&bytes);
+        TxnHash(hash)
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: 32,
+        is_fixed_size: true,
+    };
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RecentTxn {
+    pub txn_id: u128,
+    pub created_at_time: u64,
+}
+
+impl Storable for RecentTxn {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+thread_local! {
+    pub static COLLECTION_METADATA: RefCell<StableCell<CollectionMetadata, Memory>> =
+        RefCell::new(StableCell::init(get_collection_metadata_memory(), CollectionMetadata::default())
+            .expect("failed to init collection metadata cell"));
+
+    pub static TOKEN_MAP: RefCell<StableBTreeMap<u128, Token, Memory>> =
+        RefCell::new(StableBTreeMap::init(get_token_map_memory()));
+
+    pub static TOKEN_APPROVALS: RefCell<StableBTreeMap<TokenApprovalKey, ApprovalInfo, Memory>> =
+        RefCell::new(StableBTreeMap::init(get_token_approvals_memory()));
+
+    pub static COLLECTION_APPROVALS: RefCell<StableBTreeMap<CollectionApprovalKey, ApprovalInfo, Memory>> =
+        RefCell::new(StableBTreeMap::init(get_collection_approvals_memory()));
+
+    pub static RECENT_TXNS: RefCell<StableBTreeMap<TxnHash, RecentTxn, Memory>> =
+        RefCell::new(StableBTreeMap::init(get_recent_txns_memory()));
+
+    pub static BLOCK_LOG: RefCell<StableLog<Vec<u8>, Memory, Memory>> =
+        RefCell::new(StableLog::init(get_block_log_index_memory(), get_block_log_data_memory())
+            .expect("failed to init block log"));
+
+    pub static MINTERS: RefCell<StableBTreeMap<Principal, (), Memory>> =
+        RefCell::new(StableBTreeMap::init(get_minters_memory()));
+}
+
+/// A single entry in the ICRC-3 block log: an append-only, hash-chained record of every
+/// mint, transfer, and burn. `phash` is the SHA-256 of the previous block's Candid encoding,
+/// so any rewrite of history changes every hash after it.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Block {
+    pub btype: String,
+    pub token_id: u128,
+    pub from: Option<Account>,
+    pub to: Option<Account>,
+    pub memo: Option<Vec<u8>>,
+    pub ts: u64,
+    pub phash: Option<Vec<u8>>,
+}
+
+pub fn query_metadata<R>(f: impl FnOnce(&CollectionMetadata) -> R) -> R {
+    COLLECTION_METADATA.with_borrow(|cell| f(cell.get()))
+}
+
+pub fn update_metadata<R>(f: impl FnOnce(&mut CollectionMetadata) -> R) -> R {
+    COLLECTION_METADATA.with_borrow_mut(|cell| {
+        let mut data = cell.get().clone();
+        let result = f(&mut data);
+        cell.set(data).unwrap();
+        result
+    })
+}
+
+pub fn query_token_map<R>(f: impl FnOnce(&StableBTreeMap<u128, Token, Memory>) -> R) -> R {
+    TOKEN_MAP.with_borrow(f)
+}
+
+/// Appends a block to the ICRC-3 log, chaining it to the previous block's hash, and returns
+/// its index, which doubles as the transaction id for dedup/indexer purposes.
+pub fn append_block(
+    btype: &str,
+    token_id: u128,
+    from: Option<Account>,
+    to: Option<Account>,
+    memo: Option<Vec<u8>>,
+) -> u128 {
+    let phash = BLOCK_LOG.with_borrow(|log| {
+        let len = log.len();
+        (len > 0).then(|| {
+            let prev = log.get(len - 1).expect("block log entry missing");
+            Sha256::digest(&prev).to_vec()
+        })
+    });
+
+    let block = Block {
+        btype: btype.to_string(),
+        token_id,
+        from,
+        to,
+        memo,
+        ts: now(),
+        phash,
+    };
+    let encoded = Encode!(&block).unwrap();
+
+    // The tip hash doubles as this block's `phash` for whatever is appended next, so
+    // certifying it here is what makes `icrc3_get_tip_certificate` attest to the log's tip.
+    ic_cdk::api::set_certified_data(&Sha256::digest(&encoded));
+
+    BLOCK_LOG.with_borrow_mut(|log| log.append(&encoded).expect("block log append failed")) as u128
+}
+
+/// Returns the blocks in `[start, start + length)` (clamped to the log's length) plus the
+/// log's current length, matching the shape `icrc3_get_blocks` hands back to callers.
+pub fn get_blocks(start: u64, length: u64) -> (Vec<Block>, u64) {
+    BLOCK_LOG.with_borrow(|log| {
+        let log_length = log.len();
+        let end = start.saturating_add(length).min(log_length);
+        let blocks = (start..end)
+            .filter_map(|i| log.get(i))
+            .map(|bytes| Decode!(bytes.as_slice(), Block).unwrap())
+            .collect();
+        (blocks, log_length)
+    })
+}
+
+/// True if `spender` may move `token` on its owner's behalf: a non-expired token-level
+/// approval or a non-expired collection-level ("approve-all") approval from the owner.
+pub fn is_approved_spender(token: &Token, spender: Principal, now: u64) -> bool {
+    let token_approved = TOKEN_APPROVALS.with_borrow(|approvals| {
+        approvals
+            .get(&TokenApprovalKey {
+                token_id: token.id,
+                spender,
+            })
+            .is_some_and(|info| info.is_active(now))
+    });
+    if token_approved {
+        return true;
+    }
+    COLLECTION_APPROVALS.with_borrow(|approvals| {
+        approvals
+            .get(&CollectionApprovalKey {
+                owner: token.owner.owner,
+                spender,
+            })
+            .is_some_and(|info| info.is_active(now))
+    })
+}
+
+/// Clears every token-level approval on `token_id`, e.g. after a successful transfer.
+pub fn clear_token_approvals(token_id: u128) {
+    let spenders: Vec<Principal> = TOKEN_APPROVALS.with_borrow(|approvals| {
+        approvals
+            .iter()
+            .filter(|(key, _)| key.token_id == token_id)
+            .map(|(key, _)| key.spender)
+            .collect()
+    });
+    TOKEN_APPROVALS.with_borrow_mut(|approvals| {
+        for spender in spenders {
+            approvals.remove(&TokenApprovalKey { token_id, spender });
+        }
+    });
+}
+
+/// Hashes the parts of a transfer request that make two requests "the same" for dedup
+/// purposes, matching the ICRC-1/7 `created_at_time` retry convention.
+pub fn hash_transfer(
+    caller: &Principal,
+    token_id: u128,
+    to: &Account,
+    memo: &Option<Vec<u8>>,
+    created_at_time: u64,
+) -> TxnHash {
+    let mut hasher = Sha256::new();
+    hasher.update(caller.as_slice());
+    hasher.update(token_id.to_be_bytes());
+    hasher.update(to.owner.as_slice());
+    if let Some(subaccount) = &to.subaccount {
+        hasher.update(subaccount);
+    }
+    if let Some(memo) = memo {
+        hasher.update(memo);
+    }
+    hasher.update(created_at_time.to_be_bytes());
+    TxnHash(hasher.finalize().into())
+}
+
+/// Looks up a previously recorded transaction with the same dedup hash, if still within
+/// the window it was recorded under.
+pub fn find_recent_txn(hash: &TxnHash) -> Option<RecentTxn> {
+    RECENT_TXNS.with_borrow(|txns| txns.get(hash))
+}
+
+/// Records a successfully applied transfer under its dedup hash.
+pub fn record_recent_txn(hash: TxnHash, txn_id: u128, created_at_time: u64) {
+    RECENT_TXNS.with_borrow_mut(|txns| {
+        txns.insert(
+            hash,
+            RecentTxn {
+                txn_id,
+                created_at_time,
+            },
+        );
+    });
+}
+
+/// Drops dedup entries whose `created_at_time` has fallen outside the window any retry
+/// could still legally land in, so the index stays bounded.
+pub fn prune_recent_txns(now: u64, cutoff_age: u64) {
+    let cutoff = now.saturating_sub(cutoff_age);
+    let stale: Vec<TxnHash> = RECENT_TXNS.with_borrow(|txns| {
+        txns.iter()
+            .filter(|(_, txn)| txn.created_at_time < cutoff)
+            .map(|(hash, _)| hash)
+            .collect()
+    });
+    RECENT_TXNS.with_borrow_mut(|txns| {
+        for hash in stale {
+            txns.remove(&hash);
+        }
+    });
+}
+
+/// True if `principal` is the configured minting authority or a delegated minter.
+pub fn is_minter(principal: Principal) -> bool {
+    let is_minting_auth = query_metadata(|metadata| {
+        metadata
+            .minting_auth
+            .as_ref()
+            .is_some_and(|auth| auth.owner == principal)
+    });
+    is_minting_auth || MINTERS.with_borrow(|minters| minters.contains_key(&principal))
+}
+
+/// True if `principal` is the configured minting authority, the only one allowed to manage
+/// the delegated minter set.
+pub fn is_minting_authority(principal: Principal) -> bool {
+    query_metadata(|metadata| {
+        metadata
+            .minting_auth
+            .as_ref()
+            .is_some_and(|auth| auth.owner == principal)
+    })
+}
+
+pub fn add_minter(principal: Principal) {
+    MINTERS.with_borrow_mut(|minters| {
+        minters.insert(principal, ());
+    });
+}
+
+pub fn remove_minter(principal: Principal) {
+    MINTERS.with_borrow_mut(|minters| {
+        minters.remove(&principal);
+    });
+}
+
+pub fn list_minters() -> Vec<Principal> {
+    MINTERS.with_borrow(|minters| minters.iter().map(|(principal, _)| principal).collect())
+}
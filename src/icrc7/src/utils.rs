@@ -0,0 +1,3 @@
+pub fn now() -> u64 {
+    ic_cdk::api::time()
+}